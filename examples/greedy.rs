@@ -11,35 +11,17 @@ use priority_queue::PriorityQueue;
 use stride_runner_domset::pace::{graph::*, instance_reader::PaceReader};
 use structopt::StructOpt;
 
-fn read_graph() -> anyhow::Result<Vec<Vec<Node>>> {
+fn read_graph() -> anyhow::Result<CsrGraph> {
     let stdin = std::io::stdin().lock();
     let reader = PaceReader::try_new(stdin)?;
-
-    let mut closed_neighbors = (0..reader.number_of_nodes())
-        .map(|_| Vec::new())
-        .collect::<Vec<Vec<Node>>>();
-
-    let number_of_edges_according_to_header = reader.number_of_edges();
-    for edge in reader {
-        let Edge(u, v) = edge?;
-        closed_neighbors[u as usize].push(v);
-        assert_ne!(u, v);
-        closed_neighbors[v as usize].push(u);
-    }
-
-    debug_assert_eq!(
-        closed_neighbors.iter().map(|nei| nei.len()).sum::<usize>(),
-        2 * (number_of_edges_according_to_header as usize)
-    );
-
-    Ok(closed_neighbors)
+    Ok(CsrGraph::try_from_reader(reader)?)
 }
 
-fn greedy(graph: &[Vec<Node>]) -> Vec<Node> {
+fn greedy(graph: &CsrGraph) -> Vec<Node> {
     let mut pq = PriorityQueue::new();
 
-    for (node, neighbors) in graph.iter().enumerate() {
-        pq.push(node as Node, neighbors.len() as Node);
+    for node in graph.nodes() {
+        pq.push(node, graph.degree(node));
     }
 
     let mut domset = Vec::new();
@@ -50,7 +32,7 @@ fn greedy(graph: &[Vec<Node>]) -> Vec<Node> {
         }
 
         domset.push(node);
-        for neighbor in &graph[node as usize] {
+        for neighbor in graph.neighbors(node) {
             pq.change_priority_by(neighbor, |d| *d -= 1);
         }
     }
@@ -113,6 +95,36 @@ struct Opt {
 
     #[structopt(short, long)]
     empty_lines: bool,
+
+    #[structopt(
+        short,
+        long,
+        help = "Write all nodes and then the greedy solution as intermediate solutions to STRIDE_INTERMEDIATE_PATH"
+    )]
+    anytime: bool,
+}
+
+fn write_intermediate_solutions(graph: &CsrGraph, domset: &[Node]) -> anyhow::Result<()> {
+    let Some(path) = std::env::var_os("STRIDE_INTERMEDIATE_PATH") else {
+        return Ok(());
+    };
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+
+    let all_nodes: Vec<Node> = graph.nodes().collect();
+    for solution in [all_nodes.as_slice(), domset] {
+        let mut text = format!("{}\n", solution.len());
+        for u in solution {
+            text += &format!("{}\n", u + 1);
+        }
+        file.write_all(text.as_bytes())?;
+        file.flush()?;
+    }
+
+    Ok(())
 }
 
 fn wait_for_sigterm(opts: &Opt, term: Arc<AtomicBool>) -> anyhow::Result<()> {
@@ -139,13 +151,17 @@ fn main() -> anyhow::Result<()> {
     let term = Arc::new(AtomicBool::new(false));
     signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&term))?;
 
-    let adj_list = read_graph()?;
-    let mut domset = greedy(&adj_list);
+    let graph = read_graph()?;
+    let mut domset = greedy(&graph);
 
     if opts.infeasible {
         domset.truncate(domset.len() / 2);
     }
 
+    if opts.anytime {
+        write_intermediate_solutions(&graph, &domset)?;
+    }
+
     if opts.sleep > 0 {
         std::thread::sleep(Duration::from_secs(opts.sleep));
     }
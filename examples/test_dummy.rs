@@ -1,14 +1,26 @@
-use std::hint::black_box;
+use std::{hint::black_box, io::Write};
 
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
 enum Mode {
     Normal,
-    Sleep { milliseconds: u64 },
+    Sleep {
+        milliseconds: u64,
+    },
     SigTerm,
     NeverTerminate,
-    Alloc { megabytes: usize },
+    Alloc {
+        megabytes: usize,
+    },
+    Exit {
+        code: i32,
+        /// Wait for SIGTERM before exiting
+        #[structopt(long)]
+        on_sigterm: bool,
+    },
+    Abort,
+    ForkNeverTerminate,
 }
 
 fn main() {
@@ -32,11 +44,30 @@ fn main() {
             std::thread::sleep(std::time::Duration::from_millis(200));
         },
         Mode::Alloc { megabytes } => {
-            let mut vec = vec![0u8; megabytes * 1024 * 1024];
-            for (i, x) in vec.iter_mut().enumerate() {
-                *x = (i % 256) as u8;
+            // allocate in chunks, so the resident memory grows until a limit is hit
+            // (non-zero content, so every page is actually touched)
+            let chunks: Vec<_> = (0..megabytes).map(|_| vec![1u8; 1024 * 1024]).collect();
+            black_box(chunks);
+        }
+        Mode::Exit { code, on_sigterm } => {
+            while on_sigterm && !term.load(std::sync::atomic::Ordering::Relaxed) {
+                std::thread::sleep(std::time::Duration::from_millis(20));
             }
-            black_box(vec);
+            std::process::exit(code)
+        }
+        Mode::Abort => std::process::abort(),
+        Mode::ForkNeverTerminate => {
+            // spawns a copy of itself in mode `never-terminate`, prints its PID, and waits for it
+            let mut child = std::process::Command::new(std::env::current_exe().unwrap())
+                .arg("never-terminate")
+                .stdout(std::process::Stdio::null())
+                .spawn()
+                .unwrap();
+
+            println!("{}", child.id());
+            std::io::stdout().flush().unwrap();
+
+            child.wait().unwrap();
         }
     }
 }
@@ -5,10 +5,15 @@ use console::Style;
 use stride_runner_domset::{
     commands::{
         arguments::*,
+        compare::command_compare,
         export::{command_export_instance, command_export_solution},
+        flush_uploads::command_flush_uploads,
         import::command_import_solution,
+        info::command_info,
+        prefetch::command_prefetch,
         register::command_register,
         run::command_run,
+        stats::command_stats,
         update::command_update,
     },
     utils::{directory::StrideDirectory, settings::Settings},
@@ -29,7 +34,18 @@ fn parse_arguments() -> anyhow::Result<(Arguments, Vec<String>)> {
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let settings = read_and_register_settings()?; // must happen BEFORE `Arguments::from_args()` !!!
-    let (opts, child_args) = parse_arguments()?;
+    let (mut opts, child_args) = parse_arguments()?;
+    opts.common.offline |= settings.offline;
+    opts.common.no_verify_tls |= settings.no_verify_tls;
+    if opts.common.proxy.is_none() {
+        if let Some(proxy) = &settings.proxy {
+            opts.common.proxy = Some(
+                proxy
+                    .parse()
+                    .with_context(|| format!("Parsing proxy {proxy:?} of the config file"))?,
+            );
+        }
+    }
 
     if let Some(level) = opts.common.logging {
         println!("Enabled logging to file {LOG_FILE} with level {level:?}");
@@ -51,10 +67,16 @@ async fn main() -> anyhow::Result<()> {
             command_update(&opts.common, &cmd_opts).await
         }
         Commands::RunEnum(RunEnum::Run(mut cmd_opts)) => {
-            if cmd_opts.solver_binary.to_string_lossy().is_empty() {
+            if cmd_opts.resume.is_none()
+                && cmd_opts.solvers.is_none()
+                && cmd_opts.solver_binary.to_string_lossy().is_empty()
+            {
                 anyhow::bail!("Missing solver binary; please set --solver-bin");
             }
 
+            if cmd_opts.resume.is_some() && !child_args.is_empty() {
+                anyhow::bail!("Solver arguments cannot be combined with --resume; the options of the original run apply");
+            }
             cmd_opts.solver_args = child_args;
 
             if cmd_opts.solver_uuid.is_none() {
@@ -74,6 +96,19 @@ async fn main() -> anyhow::Result<()> {
         Commands::ImportSolutionEnum(ImportSolutionEnum::ImportSolution(cmd_opts)) => {
             command_import_solution(&opts.common, &cmd_opts).await
         }
+        Commands::FlushUploadsEnum(FlushUploadsEnum::FlushUploads(cmd_opts)) => {
+            command_flush_uploads(&opts.common, &cmd_opts).await
+        }
+        Commands::CompareEnum(CompareEnum::Compare(cmd_opts)) => {
+            command_compare(&opts.common, &cmd_opts).await
+        }
+        Commands::StatsEnum(StatsEnum::Stats(cmd_opts)) => {
+            command_stats(&opts.common, &cmd_opts).await
+        }
+        Commands::InfoEnum(InfoEnum::Info(cmd_opts)) => command_info(&opts.common, &cmd_opts).await,
+        Commands::PrefetchEnum(PrefetchEnum::Prefetch(cmd_opts)) => {
+            command_prefetch(&opts.common, &cmd_opts).await
+        }
     };
 
     if let Err(e) = result {
@@ -1,6 +1,7 @@
-use std::{path::PathBuf, sync::LazyLock, time::Duration};
+use std::{path::PathBuf, str::FromStr, sync::LazyLock, time::Duration};
 
 use reqwest::Url;
+use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 use tracing::level_filters::LevelFilter;
 use uuid::Uuid;
@@ -38,7 +39,32 @@ pub enum ImportSolutionEnum {
 }
 
 #[derive(StructOpt)]
-#[allow(clippy::enum_variant_names)]
+pub enum FlushUploadsEnum {
+    FlushUploads(FlushUploadsOpts),
+}
+
+#[derive(StructOpt)]
+pub enum CompareEnum {
+    Compare(CompareOpts),
+}
+
+#[derive(StructOpt)]
+pub enum StatsEnum {
+    Stats(StatsOpts),
+}
+
+#[derive(StructOpt)]
+pub enum InfoEnum {
+    Info(InfoOpts),
+}
+
+#[derive(StructOpt)]
+pub enum PrefetchEnum {
+    Prefetch(PrefetchOpts),
+}
+
+#[derive(StructOpt)]
+#[allow(clippy::enum_variant_names, clippy::large_enum_variant)]
 pub enum Commands {
     #[structopt(flatten)]
     RegisterEnum(RegisterEnum),
@@ -57,6 +83,21 @@ pub enum Commands {
 
     #[structopt(flatten)]
     ImportSolutionEnum(ImportSolutionEnum),
+
+    #[structopt(flatten)]
+    FlushUploadsEnum(FlushUploadsEnum),
+
+    #[structopt(flatten)]
+    CompareEnum(CompareEnum),
+
+    #[structopt(flatten)]
+    StatsEnum(StatsEnum),
+
+    #[structopt(flatten)]
+    InfoEnum(InfoEnum),
+
+    #[structopt(flatten)]
+    PrefetchEnum(PrefetchEnum),
 }
 
 #[derive(StructOpt)]
@@ -81,6 +122,9 @@ macro_rules! gen_default {
 
 gen_default!(SERVER_URL, server_url);
 gen_default!(RUN_LOG_DIR, run_log_dir);
+gen_default!(CONNECT_TIMEOUT, connect_timeout);
+gen_default!(READ_TIMEOUT, read_timeout);
+gen_default!(RETRIES, retries);
 
 #[derive(Clone, Debug, StructOpt)]
 pub struct CommonOpts {
@@ -100,6 +144,45 @@ pub struct CommonOpts {
         default_value = &DEFAULT_SERVER_URL
     )]
     pub server_url: Url,
+
+    #[structopt(
+        long,
+        help = "Never access the network; work from local caches only and queue uploads. Can also be enabled in the config file"
+    )]
+    pub offline: bool,
+
+    #[structopt(
+        long,
+        help = "Seconds to wait for a connection to the server; 0 waits indefinitely",
+        default_value = &DEFAULT_CONNECT_TIMEOUT
+    )]
+    pub connect_timeout: u64,
+
+    #[structopt(
+        long,
+        help = "Abort a request if no data was received for that many seconds; 0 waits indefinitely",
+        default_value = &DEFAULT_READ_TIMEOUT
+    )]
+    pub read_timeout: u64,
+
+    #[structopt(
+        long,
+        help = "Retry failed downloads that many times; the waiting time doubles after each attempt",
+        default_value = &DEFAULT_RETRIES
+    )]
+    pub retries: u32,
+
+    #[structopt(
+        long,
+        help = "Proxy for all connections to the server, e.g. http://proxy:3128. If omitted use value from config."
+    )]
+    pub proxy: Option<Url>,
+
+    #[structopt(
+        long,
+        help = "Do not verify the TLS certificate of the server (insecure). Can also be enabled in the config file"
+    )]
+    pub no_verify_tls: bool,
 }
 
 impl CommonOpts {
@@ -115,7 +198,7 @@ gen_default!(TIMEOUT, timeout);
 gen_default!(GRACE, grace);
 gen_default!(PARALLEL_JOBS, parallel_jobs);
 
-#[derive(Clone, Debug, StructOpt)]
+#[derive(Clone, Debug, StructOpt, Serialize, Deserialize)]
 pub struct RunOpts {
     #[structopt(
         short = "-b",
@@ -132,6 +215,12 @@ pub struct RunOpts {
     )]
     pub solver_uuid: Option<Uuid>,
 
+    #[structopt(
+        long,
+        help = "JSON file with named solver configurations (name, binary, args, solver_uuid) to benchmark against each other; replaces --solver-bin"
+    )]
+    pub solvers: Option<PathBuf>,
+
     #[structopt(
         short = "-T",
         long,
@@ -140,6 +229,24 @@ pub struct RunOpts {
     )]
     pub timeout: u64,
 
+    #[structopt(
+        long,
+        help = "Add that many seconds per node and edge of the instance to --timeout (rounded up)"
+    )]
+    pub timeout_per_size: Option<f64>,
+
+    #[structopt(
+        long,
+        help = "File with one `IID SECONDS` pair per line; overrides the computed timeout of the listed instances"
+    )]
+    pub timeout_file: Option<PathBuf>,
+
+    #[structopt(long, help = "Lower bound on the timeout of each instance in seconds")]
+    pub min_timeout: Option<u64>,
+
+    #[structopt(long, help = "Upper bound on the timeout of each instance in seconds")]
+    pub max_timeout: Option<u64>,
+
     #[structopt(
         short = "-G",
         long,
@@ -151,6 +258,26 @@ pub struct RunOpts {
     #[structopt(short = "-j", long, help = "Max. number of parallel solver runs", default_value=&DEFAULT_PARALLEL_JOBS)]
     pub parallel_jobs: usize,
 
+    #[structopt(
+        long,
+        help = "Run each instance that many times; each repetition gets a distinct seed via STRIDE_SEED",
+        default_value = "1"
+    )]
+    #[serde(default = "default_repetitions")]
+    pub repetitions: u32,
+
+    #[structopt(
+        long,
+        help = "Limit the address space of each solver run to that many megabytes; a solver is reported as exceeding it if it fails with an allocation error on stderr (or aborts/segfaults) after using a large part of it"
+    )]
+    pub memory_limit: Option<u64>,
+
+    #[structopt(
+        long,
+        help = "Limit the CPU time of each solver run to that many seconds; killed after additional grace period"
+    )]
+    pub cpu_limit: Option<u64>,
+
     #[structopt(
         short = "-o",
         long,
@@ -158,9 +285,44 @@ pub struct RunOpts {
     )]
     pub suboptimal_is_error: bool,
 
-    #[structopt(long, help = "Sort instance list by IID; otherwise shuffle")]
+    #[structopt(
+        long,
+        help = "Sort instance list by IID; same as `--order iid`",
+        conflicts_with = "order"
+    )]
     pub sort_instances: bool,
 
+    #[structopt(
+        long,
+        help = "Order in which instances are executed",
+        default_value = "shuffle",
+        possible_values = &InstanceOrder::VARIANTS
+    )]
+    #[serde(default)]
+    pub order: InstanceOrder,
+
+    #[structopt(
+        long,
+        help = "Size of an instance used by `--order smallest-first/largest-first`",
+        default_value = "total",
+        possible_values = &SizeMeasure::VARIANTS
+    )]
+    #[serde(default)]
+    pub size_by: SizeMeasure,
+
+    #[structopt(
+        long,
+        help = "Seed for `--order shuffle` to reproduce an instance order; random by default and stored in run.json"
+    )]
+    #[serde(alias = "seed")]
+    pub shuffle_seed: Option<u64>,
+
+    #[structopt(
+        long,
+        help = "Log directory (or its summary.csv) of an earlier run whose runtimes are used by `--order hardest-first`"
+    )]
+    pub runtimes_from: Option<PathBuf>,
+
     #[structopt(
         short = "-i",
         long,
@@ -171,7 +333,13 @@ pub struct RunOpts {
     #[structopt(
         short = "-w",
         long = "--where",
-        help = "SELECT iid FROM Instance WHERE ...; if combined with -i the intersection is taken"
+        help = "Filter on the instance metadata, e.g. \"nodes < 1000 and planar and best_score?\"; if combined with -i the intersection is taken"
+    )]
+    pub filter: Option<String>,
+
+    #[structopt(
+        long = "--where-sql",
+        help = "Raw SQL: SELECT iid FROM Instance WHERE ...; alternative to --where for expert use"
     )]
     pub sql_where: Option<String>,
 
@@ -202,10 +370,157 @@ pub struct RunOpts {
     #[structopt(short = "-c", long, help = "Remove comment lines from instances")]
     pub strip_comments: bool,
 
+    #[structopt(
+        short = "-a",
+        long,
+        help = "Track intermediate solutions the solver appends to the file in STRIDE_INTERMEDIATE_PATH; a timeout is scored (and uploaded with the time it was read) by the best of them"
+    )]
+    #[serde(default)]
+    pub anytime: bool,
+
+    #[structopt(
+        long,
+        help = "Stop the solver (SIGTERM) once an intermediate solution (see --anytime) reaches the best known score"
+    )]
+    #[serde(default)]
+    pub stop_at_best: bool,
+
+    #[structopt(
+        long,
+        help = "Write one JSON object per event (job started, state changes, job finished, uploads, run finished) to this file; use `-` for stdout"
+    )]
+    pub json_events: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        help = "Print one line per finished job instead of progress bars; default if stdout is not a terminal"
+    )]
+    #[serde(default)]
+    pub plain: bool,
+
+    #[structopt(
+        long,
+        help = "Resume an interrupted run from its log directory; the options of the original run apply, so only --plain may be given in addition",
+        conflicts_with_all = &RESUME_CONFLICTS
+    )]
+    pub resume: Option<PathBuf>,
+
     #[structopt(skip)]
     pub solver_args: Vec<String>,
 }
 
+/// Options of `run` that are stored with the run and hence cannot be changed when resuming it
+const RESUME_CONFLICTS: [&str; 30] = [
+    "solver-binary",
+    "solver-uuid",
+    "solvers",
+    "timeout",
+    "timeout-per-size",
+    "timeout-file",
+    "min-timeout",
+    "max-timeout",
+    "grace",
+    "parallel-jobs",
+    "repetitions",
+    "memory-limit",
+    "cpu-limit",
+    "suboptimal-is-error",
+    "sort-instances",
+    "order",
+    "size-by",
+    "shuffle-seed",
+    "runtimes-from",
+    "instances",
+    "filter",
+    "sql-where",
+    "export-iid-only",
+    "no-upload",
+    "no-env",
+    "keep-logs-on-success",
+    "strip-comments",
+    "anytime",
+    "stop-at-best",
+    "json-events",
+];
+
+/// Order in which the instances of a run are executed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstanceOrder {
+    #[default]
+    Shuffle,
+    Iid,
+    SmallestFirst,
+    LargestFirst,
+    /// Longest runtime in an earlier run first
+    HardestFirst,
+}
+
+impl InstanceOrder {
+    pub const VARIANTS: [&'static str; 5] = [
+        "shuffle",
+        "iid",
+        "smallest-first",
+        "largest-first",
+        "hardest-first",
+    ];
+}
+
+impl FromStr for InstanceOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "shuffle" => Self::Shuffle,
+            "iid" => Self::Iid,
+            "smallest-first" => Self::SmallestFirst,
+            "largest-first" => Self::LargestFirst,
+            "hardest-first" => Self::HardestFirst,
+            _ => anyhow::bail!("Unknown instance order {s:?}"),
+        })
+    }
+}
+
+/// Measure of an instance's size
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizeMeasure {
+    Nodes,
+    Edges,
+    /// Nodes plus edges
+    #[default]
+    Total,
+}
+
+impl SizeMeasure {
+    pub const VARIANTS: [&'static str; 3] = ["nodes", "edges", "total"];
+
+    pub fn of(self, nodes: u32, edges: u32) -> u64 {
+        match self {
+            Self::Nodes => nodes as u64,
+            Self::Edges => edges as u64,
+            Self::Total => nodes as u64 + edges as u64,
+        }
+    }
+}
+
+impl FromStr for SizeMeasure {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "nodes" => Self::Nodes,
+            "edges" => Self::Edges,
+            "total" => Self::Total,
+            _ => anyhow::bail!("Unknown size measure {s:?}"),
+        })
+    }
+}
+
+fn default_repetitions() -> u32 {
+    1
+}
+
 impl RunOpts {
     pub fn timeout_duration(&self) -> Duration {
         Duration::from_secs(self.timeout)
@@ -214,6 +529,10 @@ impl RunOpts {
     pub fn grace_duration(&self) -> Duration {
         Duration::from_secs(self.grace)
     }
+
+    pub fn cpu_limit_duration(&self) -> Option<Duration> {
+        self.cpu_limit.map(Duration::from_secs)
+    }
 }
 
 /////////////////////
@@ -290,11 +609,28 @@ pub struct ExportSolutionOpts {
     )]
     pub output: PathBuf,
 
-    #[structopt(short, long, help = "UUID of solver used to upload the solution")]
-    pub solver: Uuid,
+    #[structopt(
+        short,
+        long,
+        required_unless = "cached",
+        help = "UUID of solver used to upload the solution"
+    )]
+    pub solver: Option<Uuid>,
 
-    #[structopt(short, long, help = "UUID of the run that produced the solution")]
-    pub run: Uuid,
+    #[structopt(
+        short,
+        long,
+        required_unless = "cached",
+        help = "UUID of the run that produced the solution"
+    )]
+    pub run: Option<Uuid>,
+
+    #[structopt(
+        short,
+        long,
+        help = "Export the smallest solution found on this machine from the local cache; works offline"
+    )]
+    pub cached: bool,
 }
 
 ////////////
@@ -311,3 +647,100 @@ pub struct ImportSolutionOpts {
     )]
     pub solution: Option<PathBuf>,
 }
+
+////////////
+
+#[derive(Debug, StructOpt)]
+pub struct FlushUploadsOpts {
+    #[structopt(short, long, help = "Discard all queued uploads without sending them")]
+    pub discard: bool,
+}
+
+////////////
+
+#[derive(Debug, StructOpt)]
+pub struct CompareOpts {
+    #[structopt(help = "Log directory (or its summary.csv) of the baseline run")]
+    pub run_a: PathBuf,
+
+    #[structopt(help = "Log directory (or its summary.csv) of the run compared to the baseline")]
+    pub run_b: PathBuf,
+
+    #[structopt(
+        short,
+        long,
+        help = "List all instances; by default unchanged instances are omitted"
+    )]
+    pub all: bool,
+
+    #[structopt(long, help = "Write the per-instance comparison as CSV to this path")]
+    pub csv: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        help = "Write the comparison including aggregate statistics as JSON to this path"
+    )]
+    pub json: Option<PathBuf>,
+}
+
+////////////
+
+#[derive(Debug, StructOpt)]
+pub struct StatsOpts {
+    #[structopt(
+        short = "-w",
+        long = "--where",
+        help = "Only consider instances matching this filter (same syntax as for the run command)"
+    )]
+    pub filter: Option<String>,
+
+    #[structopt(
+        long = "--where-sql",
+        help = "Only consider instances selected by SELECT iid FROM Instance WHERE ..."
+    )]
+    pub sql_where: Option<String>,
+}
+
+////////////
+
+#[derive(Debug, StructOpt)]
+pub struct InfoOpts {
+    #[structopt(help = "Instance ID")]
+    pub instance: IId,
+
+    #[structopt(long, help = "Print the information as JSON")]
+    pub json: bool,
+}
+
+////////////
+
+#[derive(Debug, StructOpt)]
+pub struct PrefetchOpts {
+    #[structopt(
+        short = "-i",
+        long,
+        help = "Path to a file with instance list (one IID per line) to be downloaded"
+    )]
+    pub instances: Option<PathBuf>,
+
+    #[structopt(
+        short = "-w",
+        long = "--where",
+        help = "Filter on the instance metadata (same syntax as for the run command); if combined with -i the intersection is taken"
+    )]
+    pub filter: Option<String>,
+
+    #[structopt(
+        long = "--where-sql",
+        help = "Raw SQL: SELECT iid FROM Instance WHERE ...; alternative to --where for expert use"
+    )]
+    pub sql_where: Option<String>,
+
+    #[structopt(
+        short = "-j",
+        long,
+        help = "Max. number of concurrent downloads",
+        default_value = "8"
+    )]
+    pub parallel_downloads: usize,
+}
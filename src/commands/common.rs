@@ -0,0 +1,272 @@
+//! Helpers shared by several subcommands to select instances and to locate run summaries
+
+use std::{
+    collections::HashSet,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use tracing::debug;
+
+use crate::utils::{
+    instance_filter::InstanceFilter,
+    meta_data_db::{DangerousRawClause, MetaDataDB},
+    IId,
+};
+
+const PATH_SUMMARY: &str = "summary.csv";
+
+/// Path of the run summary within the log directory of a run
+pub fn summary_file_of_log_dir(log_dir: &Path) -> PathBuf {
+    log_dir.join(PATH_SUMMARY)
+}
+
+/// Accepts either the log directory of a run or the path to its summary.
+/// The log directory of a benchmark run with several solvers has no summary of its own;
+/// in this case, the error lists the per-solver summaries.
+pub fn resolve_summary_path(path: &Path) -> anyhow::Result<PathBuf> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+
+    let summary = summary_file_of_log_dir(path);
+    if !summary.is_file() {
+        let mut per_solver: Vec<_> = sub_directories(path)
+            .iter()
+            .map(|dir| summary_file_of_log_dir(dir))
+            .filter(|summary| summary.is_file())
+            .collect();
+        per_solver.sort();
+
+        if !per_solver.is_empty() {
+            anyhow::bail!(
+                "{path:?} contains a run with several solvers; pass the summary of one of them:\n{}",
+                per_solver
+                    .iter()
+                    .map(|p| format!("  {}", p.display()))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Returns the sub directories of `path`; an unreadable directory has none
+pub fn sub_directories(path: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Reads a newline separated list of instance IDs from a file.
+/// Whitespaces are trimmed from the beginning and end of each line.
+/// Lines starting with 'c' are considered comments and ignored.
+pub fn read_instance_list(path: &Path) -> anyhow::Result<Vec<IId>> {
+    let file = std::fs::File::open(path)?;
+    let reader = std::io::BufReader::new(file);
+
+    let mut instances = Vec::new();
+    for org_line in reader.lines() {
+        let org_line = org_line?;
+        let line = org_line.trim();
+
+        if line.is_empty() || line.starts_with("c") {
+            continue;
+        }
+
+        let id = line.parse::<IId>()?;
+        instances.push(id);
+    }
+
+    Ok(instances)
+}
+
+async fn check_that_instances_exist(db: &MetaDataDB, instances: &[IId]) -> anyhow::Result<()> {
+    let all_known: HashSet<IId> = db
+        .fetch_instance_iids_from_db(DangerousRawClause("1=1"))
+        .await?
+        .into_iter()
+        .collect();
+    let proposed: HashSet<IId> = instances.iter().cloned().collect();
+
+    if !proposed.is_subset(&all_known) {
+        let mut difference: Vec<_> = proposed.difference(&all_known).collect();
+        difference.sort();
+        let len = difference.len();
+        let diff_str: Vec<_> = difference
+            .into_iter()
+            .take(20)
+            .map(|iid| iid.iid_to_u32().to_string())
+            .collect();
+        let diff_str = diff_str.join(", ");
+
+        anyhow::bail!("List contains {len} instance ids not found in metadata.db; try run `stride-runner update`. At least following IDs were not found {diff_str}");
+    }
+
+    Ok(())
+}
+
+/// Selects the instances given by an instance list (`-i`), a filter (`--where`), or a
+/// raw SQL clause (`--where-sql`); if a list and a query are given, the intersection is taken
+pub async fn select_instances(
+    meta_db: &MetaDataDB,
+    instance_list: Option<&Path>,
+    filter: Option<&str>,
+    sql_where: Option<&str>,
+) -> anyhow::Result<Vec<IId>> {
+    select_instances_if_given(meta_db, instance_list, filter, sql_where)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Must provide --instances, --where, and/or --where-sql"))
+}
+
+/// Same as [`select_instances`], but returns `None` if no selection is given
+pub async fn select_instances_if_given(
+    meta_db: &MetaDataDB,
+    instance_list: Option<&Path>,
+    filter: Option<&str>,
+    sql_where: Option<&str>,
+) -> anyhow::Result<Option<Vec<IId>>> {
+    if filter.is_some() && sql_where.is_some() {
+        anyhow::bail!("--where and --where-sql cannot be combined");
+    }
+
+    let instances_from_file = match instance_list {
+        Some(path) => {
+            let instances = read_instance_list(path)?;
+            debug!("Read {} instances from {:?}", instances.len(), path);
+            check_that_instances_exist(meta_db, &instances).await?;
+            Some(instances)
+        }
+        None => None,
+    };
+
+    let instances_from_db = match (filter, sql_where) {
+        (Some(filter), _) => {
+            let parsed = InstanceFilter::parse(filter)
+                .map_err(|e| anyhow::anyhow!("Invalid filter --where {filter:?}: {e}"))?;
+            let instances = meta_db.fetch_instance_iids_by_filter(&parsed).await?;
+            debug!(
+                "Read {} instances from InstanceDB where {}",
+                instances.len(),
+                filter
+            );
+            Some(instances)
+        }
+        (None, Some(where_clause)) => {
+            let instances = meta_db
+                .fetch_instance_iids_from_db(DangerousRawClause(where_clause))
+                .await?;
+            debug!(
+                "Read {} instances from InstanceDB where {}",
+                instances.len(),
+                where_clause
+            );
+            Some(instances)
+        }
+        (None, None) => None,
+    };
+
+    Ok(match (instances_from_file, instances_from_db) {
+        (Some(file), Some(db)) => {
+            let file: HashSet<_> = file.into_iter().collect();
+            let db: HashSet<_> = db.into_iter().collect();
+            Some(file.intersection(&db).cloned().collect())
+        }
+        (Some(file), None) => Some(file),
+        (None, Some(db)) => Some(db),
+        (None, None) => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    const PREFIX: &str = "common-test";
+
+    #[test]
+    fn resolve_summary_path() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let run_dir = tmp_dir.path().join("run");
+        std::fs::create_dir(&run_dir).unwrap();
+
+        let summary = tmp_dir.path().join("summary.csv");
+        assert_eq!(super::resolve_summary_path(&summary).unwrap(), summary);
+        assert_eq!(
+            super::resolve_summary_path(&run_dir).unwrap(),
+            run_dir.join("summary.csv")
+        );
+
+        // benchmark run with several solvers
+        for solver in ["greedy", "exact"] {
+            let solver_dir = run_dir.join(solver);
+            std::fs::create_dir(&solver_dir).unwrap();
+            std::fs::write(solver_dir.join("summary.csv"), "iid\n").unwrap();
+        }
+
+        let error = super::resolve_summary_path(&run_dir)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("several solvers"), "{error}");
+        assert!(
+            error.contains(
+                &run_dir
+                    .join("exact")
+                    .join("summary.csv")
+                    .display()
+                    .to_string()
+            ),
+            "{error}"
+        );
+        assert!(
+            error.contains(
+                &run_dir
+                    .join("greedy")
+                    .join("summary.csv")
+                    .display()
+                    .to_string()
+            ),
+            "{error}"
+        );
+
+        // a single solver of the run can be selected
+        assert_eq!(
+            super::resolve_summary_path(&run_dir.join("exact")).unwrap(),
+            run_dir.join("exact").join("summary.csv")
+        );
+    }
+
+    #[test]
+    fn read_instance_list() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let instances_file = tmp_dir.path().join("instances.txt");
+
+        // write some instances to the file
+        {
+            let mut file = std::fs::File::create(&instances_file).unwrap();
+            writeln!(file, "c comment").unwrap();
+            writeln!(file, " 1").unwrap();
+            writeln!(file).unwrap();
+            writeln!(file, "712 ").unwrap();
+            writeln!(file, " 4").unwrap();
+            writeln!(file, "  ").unwrap();
+            writeln!(file, "c comment").unwrap();
+            writeln!(file, "5").unwrap();
+        }
+
+        let instances = super::read_instance_list(&instances_file).unwrap();
+        assert_eq!(
+            instances,
+            vec![IId::new(1), IId::new(712), IId::new(4), IId::new(5)]
+        );
+    }
+}
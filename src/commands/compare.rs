@@ -0,0 +1,141 @@
+use anyhow::Context;
+use console::Style;
+
+use crate::utils::{
+    run_comparison::{Change, RunComparison},
+    run_summary_logger::RunSummaryLogger,
+};
+
+use super::{
+    arguments::{CommonOpts, CompareOpts},
+    common::resolve_summary_path,
+};
+
+fn change_style(change: Change) -> Style {
+    match change {
+        Change::Better | Change::NewlySucceeded => Style::new().green(),
+        Change::Worse | Change::NewlyFailed => Style::new().red(),
+        Change::Unchanged | Change::BothFailed => Style::new(),
+    }
+}
+
+pub async fn command_compare(
+    _common_opts: &CommonOpts,
+    cmd_opts: &CompareOpts,
+) -> anyhow::Result<()> {
+    let run_a = RunSummaryLogger::read_entries(&resolve_summary_path(&cmd_opts.run_a)?)?;
+    let run_b = RunSummaryLogger::read_entries(&resolve_summary_path(&cmd_opts.run_b)?)?;
+    let comparison = RunComparison::new(&run_a, &run_b);
+
+    print_table(&comparison, cmd_opts.all);
+    print_stats(&comparison);
+
+    if let Some(path) = &cmd_opts.csv {
+        let file =
+            std::fs::File::create(path).with_context(|| format!("Creating CSV file {path:?}"))?;
+        comparison.write_csv(std::io::BufWriter::new(file))?;
+        println!("Wrote CSV to {}", path.display());
+    }
+
+    if let Some(path) = &cmd_opts.json {
+        let file =
+            std::fs::File::create(path).with_context(|| format!("Creating JSON file {path:?}"))?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), &comparison)?;
+        println!("Wrote JSON to {}", path.display());
+    }
+
+    Ok(())
+}
+
+fn print_table(comparison: &RunComparison, all: bool) {
+    fn opt<T: ToString>(x: Option<T>) -> String {
+        x.map_or_else(|| "-".into(), |x| x.to_string())
+    }
+
+    let rows: Vec<_> = comparison
+        .instances
+        .iter()
+        .filter(|inst| all || !matches!(inst.change, Change::Unchanged | Change::BothFailed))
+        .collect();
+
+    if rows.is_empty() {
+        println!("No instance changed its result");
+        return;
+    }
+
+    println!(
+        "{:>8} {:>4} {:<16} {:<14} {:<14} {:>8} {:>8} {:>7} {:>10} {:>10} {:>7}",
+        "IID",
+        "Rep",
+        "Change",
+        "State A",
+        "State B",
+        "Score A",
+        "Score B",
+        "Delta",
+        "Time A",
+        "Time B",
+        "Ratio"
+    );
+
+    for inst in rows {
+        let line = format!(
+            "{:>8} {:>4} {:<16} {:<14} {:<14} {:>8} {:>8} {:>7} {:>10.2} {:>10.2} {:>7}",
+            inst.iid.iid_to_u32(),
+            inst.repetition,
+            inst.change.to_string(),
+            inst.state_a,
+            inst.state_b,
+            opt(inst.score_a),
+            opt(inst.score_b),
+            opt(inst.score_delta.map(|d| format!("{d:+}"))),
+            inst.time_sec_a,
+            inst.time_sec_b,
+            opt(inst.time_ratio.map(|r| format!("{r:.2}"))),
+        );
+        println!("{}", change_style(inst.change).apply_to(line));
+    }
+    println!();
+}
+
+fn print_stats(comparison: &RunComparison) {
+    let stats = &comparison.stats;
+
+    println!(
+        "Jobs (instance and repetition) in both runs: {} (only in A: {}, only in B: {})",
+        stats.common, stats.only_in_a, stats.only_in_b
+    );
+
+    let parts = [
+        (Change::Better, stats.better),
+        (Change::Worse, stats.worse),
+        (Change::NewlySucceeded, stats.newly_succeeded),
+        (Change::NewlyFailed, stats.newly_failed),
+        (Change::Unchanged, stats.unchanged),
+        (Change::BothFailed, stats.both_failed),
+    ]
+    .map(|(change, num)| {
+        let text = format!("{change}: {num}");
+        if num == 0 {
+            text
+        } else {
+            change_style(change).apply_to(text).to_string()
+        }
+    });
+    println!("{}", parts.join(" | "));
+
+    println!(
+        "Total score (solved in both): {} -> {} ({:+})",
+        stats.score_sum_a,
+        stats.score_sum_b,
+        stats.score_sum_b as i64 - stats.score_sum_a as i64
+    );
+    println!(
+        "Total time: {:.1}s -> {:.1}s; geometric mean of time ratios (solved in both): {}",
+        stats.time_sec_sum_a,
+        stats.time_sec_sum_b,
+        stats
+            .time_ratio_geo_mean
+            .map_or_else(|| "-".into(), |r| format!("{r:.3}"))
+    );
+}
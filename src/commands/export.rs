@@ -1,14 +1,11 @@
-use std::{
-    io::Write,
-    path::{Path, PathBuf},
-};
+use std::{io::Write, path::Path};
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
 use crate::utils::{
     directory::StrideDirectory, download_progress_bar::DownloadProgressBar,
     instance_data_db::InstanceDataDB, meta_data_db::MetaDataDB,
-    server_connection::ServerConnection,
+    server_connection::ServerConnection, solution_cache_db::SolutionCacheDB,
 };
 
 use super::arguments::{CommonOpts, ExportInstanceOpts, ExportSolutionOpts};
@@ -62,7 +59,7 @@ pub async fn command_export_instance(
         .await?;
 
     let destination = if let Some(path) = cmd_opts.output.clone() {
-        if path == PathBuf::from("/") {
+        if path == Path::new("/") {
             Path::new(&cmd_opts.instance.iid_to_u32().to_string())
                 .with_extension("gr")
                 .to_path_buf()
@@ -92,12 +89,21 @@ pub async fn command_export_solution(
     common_opts: &CommonOpts,
     cmd_opts: &ExportSolutionOpts,
 ) -> anyhow::Result<()> {
+    if cmd_opts.cached {
+        return export_cached_solution(cmd_opts).await;
+    }
+
+    // structopt ensures both are present unless --cached is set
+    let (Some(solver), Some(run)) = (cmd_opts.solver, cmd_opts.run) else {
+        anyhow::bail!("Solver and run UUIDs are required unless --cached is set");
+    };
+
     let server_conn = ServerConnection::new_from_opts(common_opts)?;
     let search_path = format!(
         "api/solutions/download?iid={}&solver={}&run={}",
         cmd_opts.instance.iid_to_u32(),
-        cmd_opts.solver,
-        cmd_opts.run
+        solver,
+        run
     );
 
     download(
@@ -108,3 +114,34 @@ pub async fn command_export_solution(
     )
     .await
 }
+
+async fn export_cached_solution(cmd_opts: &ExportSolutionOpts) -> anyhow::Result<()> {
+    let stride_dir = StrideDirectory::try_default()?;
+    let solution_cache = SolutionCacheDB::new(stride_dir.db_cache_file().as_path()).await?;
+
+    let Some(cached) = solution_cache.best_solution(cmd_opts.instance).await? else {
+        anyhow::bail!(
+            "No solution for instance {} in local cache",
+            cmd_opts.instance.iid_to_u32()
+        );
+    };
+
+    let destination = cmd_opts.output.as_path();
+    if !cmd_opts.force && destination.exists() {
+        anyhow::bail!(
+            "File already exists: {}; change output path or use -f/--force to overwrite",
+            destination.display()
+        );
+    }
+
+    let mut file = std::fs::File::create(destination)?;
+    file.write_all(cached.data.as_bytes())?;
+
+    println!(
+        "Stored solution with cardinality {} of run {} to: {}",
+        cached.score,
+        cached.run_uuid,
+        destination.display()
+    );
+    Ok(())
+}
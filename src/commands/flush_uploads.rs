@@ -0,0 +1,50 @@
+use console::Style;
+
+use crate::utils::{
+    directory::StrideDirectory, server_connection::ServerConnection,
+    solution_cache_db::SolutionCacheDB, upload_queue::UploadQueue,
+};
+
+use super::arguments::{CommonOpts, FlushUploadsOpts};
+
+pub async fn command_flush_uploads(
+    common_opts: &CommonOpts,
+    cmd_opts: &FlushUploadsOpts,
+) -> anyhow::Result<()> {
+    let stride_dir = StrideDirectory::try_default()?;
+    let queue = UploadQueue::new(stride_dir.db_upload_queue_file().as_path()).await?;
+
+    if cmd_opts.discard {
+        let discarded = queue.clear().await?;
+        println!("Discarded {discarded} queued uploads");
+        return Ok(());
+    }
+
+    let queued = queue.len().await?;
+    if queued == 0 {
+        println!("No uploads queued. Done");
+        return Ok(());
+    }
+
+    let server_conn = ServerConnection::new_from_opts(common_opts)?;
+    server_conn.ensure_online(&format!("send {queued} queued uploads"))?;
+
+    println!("Sending {queued} queued uploads");
+    let solution_cache = SolutionCacheDB::new(stride_dir.db_cache_file().as_path()).await?;
+    let summary = queue.flush(&server_conn, &solution_cache).await?;
+
+    println!(
+        "Accepted: {}, rejected by server: {}",
+        Style::new().green().apply_to(summary.accepted),
+        Style::new().red().apply_to(summary.rejected)
+    );
+
+    if summary.remaining > 0 {
+        anyhow::bail!(
+            "Server not reachable; {} uploads remain queued",
+            summary.remaining
+        );
+    }
+
+    Ok(())
+}
@@ -7,14 +7,19 @@ use tracing::{debug, info, trace};
 use uuid::Uuid;
 
 use crate::{
-    pace::{instance_reader::PaceReader, Solution},
+    pace::{graph::CsrGraph, instance_reader::PaceReader, Solution},
     utils::{
         directory::StrideDirectory,
         instance_data_db::InstanceDataDB,
         meta_data_db::MetaDataDB,
         server_connection::ServerConnection,
-        solution_upload::{is_score_good_enough_for_upload, SolutionUploadRequestBuilder},
+        solution_cache_db::SolutionCacheDB,
+        solution_upload::{
+            is_score_good_enough_for_upload, send_upload_request, SolutionUploadRequestBuilder,
+            UploadStatus,
+        },
         solver_executor::SolverResult,
+        upload_queue::UploadQueue,
     },
 };
 
@@ -34,10 +39,10 @@ pub async fn command_import_solution(
     let solution = if let Some(path) = &cmd_opts.solution {
         trace!("Reading solution from file {:?}", path);
         let file = File::open(path)?;
-        Solution::read(BufReader::new(file), Some(instance_info.nodes))
+        Solution::read(BufReader::new(file), None)
     } else {
         trace!("Reading solution from stdin");
-        Solution::read(std::io::stdin().lock(), Some(instance_info.nodes))
+        Solution::read(std::io::stdin().lock(), None)
     }
     .with_context(|| "Reading solution")?;
 
@@ -51,22 +56,20 @@ pub async fn command_import_solution(
             .await?;
         let reader = PaceReader::try_new(data.as_bytes())
             .with_context(|| "Creating reader for instance data")?;
-        let num_nodes = reader.number_of_nodes();
-        let mut edges = Vec::with_capacity(reader.number_of_edges() as usize);
-        for e in reader {
-            edges.push(e.with_context(|| "Reading instance data")?);
-        }
+        let graph = CsrGraph::try_from_reader(reader).with_context(|| "Reading instance data")?;
         trace!(
-            "Read {num_nodes} nodes and {} edges from instance data",
-            edges.len()
+            "Read {} nodes and {} edges from instance data",
+            graph.number_of_nodes(),
+            graph.number_of_edges()
         );
 
-        let is_valid = solution
-            .valid_domset_for_instance(instance_info.nodes, edges.into_iter())
-            .with_context(|| "Verifying solution")?;
-
-        if !is_valid {
-            anyhow::bail!("Solution is not valid for instance {:?}", cmd_opts.instance);
+        let report = solution.verify(&graph);
+        if !report.is_feasible() {
+            anyhow::bail!(
+                "Solution is not valid for instance {:?}: {}",
+                cmd_opts.instance,
+                report.summary()
+            );
         }
     }
     println!(
@@ -76,7 +79,27 @@ pub async fn command_import_solution(
         solution.solution.len(),
     );
 
+    let solution_cache = SolutionCacheDB::new(stride_dir.db_cache_file().as_path()).await?;
+    let run_uuid = Uuid::new_v4();
+
+    if solution_cache
+        .was_uploaded(cmd_opts.instance, &solution)
+        .await?
+    {
+        println!(
+            "{}",
+            Style::new()
+                .yellow()
+                .apply_to("An identical solution was already uploaded")
+        );
+        return Ok(());
+    }
+
     if !is_score_good_enough_for_upload(solution.solution.len() as u32, instance_info.best_score) {
+        solution_cache
+            .insert(cmd_opts.instance, None, run_uuid, &solution, false)
+            .await?;
+
         println!(
             "{}. Best known score: {}",
             Style::new()
@@ -89,19 +112,47 @@ pub async fn command_import_solution(
 
     // upload solution
     let result = SolverResult::Valid {
-        data: solution.take_1indexed_solution(),
+        data: solution.solution.iter().map(|&u| u + 1).collect(),
     };
 
-    SolutionUploadRequestBuilder::default()
+    let json = SolutionUploadRequestBuilder::default()
         .instance_id(cmd_opts.instance)
-        .run_uuid(Uuid::new_v4())
+        .run_uuid(run_uuid)
         .solver_uuid(None)
         .result(&result)
         .build()
         .unwrap()
-        .upload(&server_conn)
+        .to_json()?;
+
+    let uploaded = match send_upload_request(&server_conn, cmd_opts.instance, &json).await {
+        UploadStatus::Accepted => {
+            println!("Upload complete");
+            true
+        }
+        UploadStatus::Rejected => anyhow::bail!("Server rejected solution"),
+        UploadStatus::Failed => {
+            UploadQueue::new(stride_dir.db_upload_queue_file().as_path())
+                .await?
+                .push(
+                    cmd_opts.instance,
+                    &json,
+                    Some(solution.compute_digest().as_slice()),
+                )
+                .await?;
+            println!(
+                "{}",
+                Style::new().yellow().apply_to(
+                    "Server not reachable; queued upload. Send it later using `flush-uploads`"
+                )
+            );
+            // the queued upload may still be rejected or discarded
+            false
+        }
+    };
+
+    solution_cache
+        .insert(cmd_opts.instance, None, run_uuid, &solution, uploaded)
         .await?;
 
-    println!("Upload complete");
     Ok(())
 }
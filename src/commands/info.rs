@@ -0,0 +1,299 @@
+use std::path::Path;
+
+use anyhow::Context;
+use console::Style;
+use serde::Serialize;
+use tracing::debug;
+
+use crate::{
+    pace::{graph::CsrGraph, instance_reader::PaceReader},
+    utils::{
+        directory::StrideDirectory,
+        instance_data_db::InstanceDataDB,
+        meta_data_db::{InstanceModel, MetaDataDB},
+        run_summary_logger::RunSummaryLogger,
+        server_connection::ServerConnection,
+        IId,
+    },
+};
+
+use super::{
+    arguments::{CommonOpts, InfoOpts},
+    common::{sub_directories, summary_file_of_log_dir},
+};
+
+#[derive(Debug, Serialize)]
+struct InstanceInfo {
+    #[serde(flatten)]
+    instance: InstanceModel,
+    /// Whether the instance data was stored locally before this command was executed
+    cached: bool,
+    /// `None` if the instance data could not be obtained
+    graph: Option<GraphStats>,
+    past_results: Vec<PastResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct GraphStats {
+    min_degree: u32,
+    max_degree: u32,
+    avg_degree: f64,
+    isolated_nodes: u32,
+    connected_components: u32,
+}
+
+impl GraphStats {
+    fn new(graph: &CsrGraph) -> Self {
+        let degrees = graph.nodes().map(|u| graph.degree(u));
+        let n = graph.number_of_nodes();
+
+        Self {
+            min_degree: degrees.clone().min().unwrap_or(0),
+            max_degree: degrees.clone().max().unwrap_or(0),
+            avg_degree: if n == 0 {
+                0.0
+            } else {
+                degrees.clone().map(u64::from).sum::<u64>() as f64 / n as f64
+            },
+            isolated_nodes: degrees.filter(|&d| d == 0).count() as u32,
+            connected_components: graph.number_of_connected_components(),
+        }
+    }
+}
+
+/// A result of the instance found in the summary of an earlier run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct PastResult {
+    /// Log directory of the run relative to the run log directory
+    run: String,
+    repetition: u32,
+    state: String,
+    score: Option<u32>,
+    time_sec: f64,
+}
+
+pub async fn command_info(common_opts: &CommonOpts, cmd_opts: &InfoOpts) -> anyhow::Result<()> {
+    let stride_dir = StrideDirectory::try_default()?;
+    let meta_db = MetaDataDB::new(stride_dir.db_meta_file().as_path()).await?;
+    let instance = meta_db.fetch_instance(cmd_opts.instance).await?;
+
+    let instance_db = InstanceDataDB::new(stride_dir.db_instance_file().as_path()).await?;
+    let cached = instance_db.contains(instance.data_did).await?;
+
+    let graph = match fetch_graph_stats(common_opts, &instance_db, &instance).await {
+        Ok(stats) => Some(stats),
+        Err(e) => {
+            eprintln!(
+                "{} {e:#}",
+                Style::new()
+                    .yellow()
+                    .apply_to("Cannot compute graph statistics:")
+            );
+            None
+        }
+    };
+
+    let past_results = find_past_results(&common_opts.run_log_dir, cmd_opts.instance);
+
+    let info = InstanceInfo {
+        instance,
+        cached,
+        graph,
+        past_results,
+    };
+
+    if cmd_opts.json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        print_info(&info);
+    }
+
+    Ok(())
+}
+
+async fn fetch_graph_stats(
+    common_opts: &CommonOpts,
+    instance_db: &InstanceDataDB,
+    instance: &InstanceModel,
+) -> anyhow::Result<GraphStats> {
+    let server_conn = ServerConnection::new_from_opts(common_opts)?;
+    let data = instance_db
+        .fetch_data_with_did(&server_conn, instance.iid, instance.data_did)
+        .await?;
+
+    let reader = PaceReader::try_new(data.as_bytes())
+        .with_context(|| "Creating reader for instance data")?;
+    let graph = CsrGraph::try_from_reader(reader).with_context(|| "Reading instance data")?;
+
+    Ok(GraphStats::new(&graph))
+}
+
+/// Scans the summaries of all runs in `log_base` (including the per-solver summaries of
+/// benchmark runs) for results of `iid`; unreadable summaries are skipped.
+fn find_past_results(log_base: &Path, iid: IId) -> Vec<PastResult> {
+    let mut summaries = Vec::new();
+    for run_dir in sub_directories(log_base) {
+        summaries.extend(sub_directories(&run_dir));
+        summaries.push(run_dir);
+    }
+
+    let mut results = Vec::new();
+    for dir in summaries {
+        let path = summary_file_of_log_dir(&dir);
+        if !path.is_file() {
+            continue;
+        }
+
+        let entries = match RunSummaryLogger::read_entries(&path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Skipping run summary {path:?}: {e}");
+                continue;
+            }
+        };
+
+        let run = dir
+            .strip_prefix(log_base)
+            .unwrap_or(&dir)
+            .to_string_lossy()
+            .into_owned();
+
+        results.extend(
+            entries
+                .into_iter()
+                .filter(|entry| entry.iid == iid)
+                .map(|entry| PastResult {
+                    run: run.clone(),
+                    repetition: entry.repetition,
+                    state: entry.state,
+                    score: entry.score,
+                    time_sec: entry.time_sec,
+                }),
+        );
+    }
+
+    // directories of runs start with their timestamp
+    results.sort_by(|a, b| a.run.cmp(&b.run).then(a.repetition.cmp(&b.repetition)));
+    results
+}
+
+fn print_info(info: &InstanceInfo) {
+    fn opt<T: ToString>(x: Option<T>) -> String {
+        x.map_or_else(|| "unknown".into(), |x| x.to_string())
+    }
+
+    let bold = Style::new().bold();
+    let instance = &info.instance;
+
+    println!(
+        "{} {}",
+        bold.apply_to("Instance"),
+        instance.iid.iid_to_u32()
+    );
+    println!("Data DID:          {}", instance.data_did.did_to_u32());
+    println!("Nodes:             {}", instance.nodes);
+    println!("Edges:             {}", instance.edges);
+    println!("Best score:        {}", opt(instance.best_score));
+    println!("Diameter:          {}", opt(instance.diameter));
+    println!("Treewidth:         {}", opt(instance.treewidth));
+    println!("Planar:            {}", opt(instance.planar));
+    println!("Bipartite:         {}", opt(instance.bipartite));
+    println!(
+        "Cached locally:    {}",
+        if info.cached { "yes" } else { "no" }
+    );
+
+    if let Some(graph) = &info.graph {
+        println!();
+        println!("{}", bold.apply_to("Graph"));
+        println!(
+            "Degree:            min {}, max {}, avg {:.2}",
+            graph.min_degree, graph.max_degree, graph.avg_degree
+        );
+        println!("Isolated nodes:    {}", graph.isolated_nodes);
+        println!("Components:        {}", graph.connected_components);
+    }
+
+    println!();
+    if info.past_results.is_empty() {
+        println!("No results found in local run logs");
+        return;
+    }
+
+    println!(
+        "{}",
+        bold.apply_to(format!(
+            "{:<50} {:>4} {:<14} {:>8} {:>10}",
+            "Run", "Rep", "State", "Score", "Time"
+        ))
+    );
+    for result in &info.past_results {
+        println!(
+            "{:<50} {:>4} {:<14} {:>8} {:>9.2}s",
+            result.run,
+            result.repetition,
+            result.state,
+            result.score.map_or_else(|| "-".into(), |s| s.to_string()),
+            result.time_sec
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pace::graph::Edge;
+    use tempdir::TempDir;
+
+    #[test]
+    fn graph_stats() {
+        let graph = CsrGraph::try_from_edges(5, &[Edge(0, 1), Edge(0, 2), Edge(0, 3)]).unwrap();
+
+        assert_eq!(
+            GraphStats::new(&graph),
+            GraphStats {
+                min_degree: 0,
+                max_degree: 3,
+                avg_degree: 1.2,
+                isolated_nodes: 1,
+                connected_components: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn find_past_results() {
+        let tmp_dir = TempDir::new("info-test").unwrap();
+        let base = tmp_dir.path();
+
+        let write = |dir: &str, content: &str| {
+            let dir = base.join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("summary.csv"), content).unwrap();
+        };
+
+        write(
+            "run_b",
+            "iid,repetition,time_sec,state,score\n1,0,1.5,best,3\n2,0,2.0,timeout,\n1,1,1.0,suboptimal,4\n",
+        );
+        write("run_a/greedy", "iid,time_sec,state,score\n1,0.5,best,3\n");
+        write("run_c", "garbage\n1,2,3\n");
+
+        let results = super::find_past_results(base, IId::new(1));
+        let summary: Vec<_> = results
+            .iter()
+            .map(|r| (r.run.as_str(), r.repetition, r.score))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("run_a/greedy", 0, Some(3)),
+                ("run_b", 0, Some(3)),
+                ("run_b", 1, Some(4))
+            ]
+        );
+
+        assert!(super::find_past_results(base, IId::new(3)).is_empty());
+        assert!(super::find_past_results(&base.join("missing"), IId::new(1)).is_empty());
+    }
+}
@@ -1,6 +1,12 @@
 pub mod arguments;
+pub mod common;
+pub mod compare;
 pub mod export;
+pub mod flush_uploads;
 pub mod import;
+pub mod info;
+pub mod prefetch;
 pub mod register;
 pub mod run;
+pub mod stats;
 pub mod update;
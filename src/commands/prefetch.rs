@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use console::Style;
+use futures_util::{stream, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
+use tracing::debug;
+
+use crate::utils::{
+    directory::StrideDirectory, instance_data_db::InstanceDataDB, meta_data_db::MetaDataDB,
+    server_connection::ServerConnection, DId, IId,
+};
+
+use super::{
+    arguments::{CommonOpts, PrefetchOpts},
+    common::select_instances,
+};
+
+pub async fn command_prefetch(
+    common_opts: &CommonOpts,
+    cmd_opts: &PrefetchOpts,
+) -> anyhow::Result<()> {
+    if cmd_opts.parallel_downloads == 0 {
+        anyhow::bail!("--parallel-downloads must be at least 1");
+    }
+
+    let stride_dir = StrideDirectory::try_default()?;
+    let meta_db = MetaDataDB::new(stride_dir.db_meta_file().as_path()).await?;
+    let instance_db = InstanceDataDB::new(stride_dir.db_instance_file().as_path()).await?;
+    let server_conn = ServerConnection::new_from_opts(common_opts)?;
+
+    let selected = select_instances(
+        &meta_db,
+        cmd_opts.instances.as_deref(),
+        cmd_opts.filter.as_deref(),
+        cmd_opts.sql_where.as_deref(),
+    )
+    .await?;
+
+    let dids: HashMap<IId, DId> = meta_db
+        .fetch_all_instances()
+        .await?
+        .into_iter()
+        .map(|instance| (instance.iid, instance.data_did))
+        .collect();
+    let cached = instance_db.fetch_cached_dids().await?;
+
+    // several instances may share their data; each data set is downloaded only once
+    let mut missing: BTreeMap<DId, Vec<IId>> = BTreeMap::new();
+    for iid in &selected {
+        let did = *dids
+            .get(iid)
+            .ok_or_else(|| anyhow::anyhow!("Instance {iid:?} not found in metadata.db"))?;
+        if !cached.contains(&did) {
+            missing.entry(did).or_default().push(*iid);
+        }
+    }
+
+    let num_missing: usize = missing.values().map(|iids| iids.len()).sum();
+    println!(
+        "Selected {} instances; {} already cached, {} missing",
+        selected.len(),
+        selected.len() - num_missing,
+        num_missing
+    );
+    if missing.is_empty() {
+        println!("Nothing to download. Done");
+        return Ok(());
+    }
+    server_conn.ensure_online("download missing instances")?;
+
+    let pb = ProgressBar::new(missing.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("Downloading [{elapsed_precise}] [{bar:50.green/grey}] {human_pos} of {human_len} (est: {eta})")?
+            .progress_chars("#>-"),
+    );
+
+    let mut failures: Vec<(DId, anyhow::Error)> = stream::iter(missing.iter())
+        .map(|(&did, iids)| {
+            let (instance_db, server_conn, pb) = (&instance_db, &server_conn, &pb);
+            async move {
+                let result = instance_db
+                    .fetch_data_with_did(server_conn, iids[0], did)
+                    .await;
+                pb.inc(1);
+                result.err().map(|e| (did, e))
+            }
+        })
+        .buffer_unordered(cmd_opts.parallel_downloads)
+        .filter_map(|failure| async move { failure })
+        .collect()
+        .await;
+
+    pb.finish_and_clear();
+
+    if failures.is_empty() {
+        println!(
+            "{} all {} missing instances",
+            Style::new().green().apply_to("Downloaded"),
+            num_missing
+        );
+        return Ok(());
+    }
+
+    failures.sort_by_key(|(did, _)| *did);
+    for (did, e) in &failures {
+        debug!("Failed to fetch {did:?}: {e:#}");
+    }
+
+    let failed_dids: HashSet<DId> = failures.iter().map(|(did, _)| *did).collect();
+    let mut failed: Vec<IId> = missing
+        .iter()
+        .filter(|(did, _)| failed_dids.contains(did))
+        .flat_map(|(_, iids)| iids.iter().copied())
+        .collect();
+    failed.sort();
+
+    println!(
+        "{} {} of {} missing instances; first error: {:#}",
+        Style::new().red().apply_to("Failed to fetch"),
+        failed.len(),
+        num_missing,
+        failures[0].1
+    );
+    println!(
+        "IIDs not fetched: {}",
+        failed
+            .iter()
+            .map(|iid| iid.iid_to_u32().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    anyhow::bail!("{} instances could not be fetched", failed.len());
+}
@@ -1,74 +1,185 @@
-use std::{sync::Arc, task::Poll, time::Duration};
-use tokio::time::Instant;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Poll,
+    time::Duration,
+};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    time::Instant,
+};
 
 use crate::{
     commands::{
         arguments::{CommonOpts, RunOpts},
         run::{
             context::RunContext,
-            display::{ProgressDisplay, RunnerProgressBar},
+            display::{human_println, new_display, RunDisplay},
             job::{Job, JobResult, JobResultState},
+            schedule::JobSpec,
         },
     },
-    utils::{run_summary_logger::RunSummaryLogger, IId},
+    utils::{event_logger::RunEvent, run_summary_logger::RunSummaryLogger},
 };
 
 const DEFAULT_WAIT_TIME: Duration = Duration::from_millis(100);
 const SHORT_WAIT_TIME: Duration = Duration::from_millis(10);
 
 pub async fn command_run(common_opts: &CommonOpts, cmd_opts: &RunOpts) -> anyhow::Result<()> {
-    if !cmd_opts.solver_binary.is_file() {
+    if cmd_opts.resume.is_none() && cmd_opts.solvers.is_none() && !cmd_opts.solver_binary.is_file()
+    {
         anyhow::bail!("Solver binary {:?} not found", cmd_opts.solver_binary);
     }
 
+    let resumed = cmd_opts.resume.is_some();
+    // a display choice of this invocation; not taken from the original run when resuming
+    let plain = cmd_opts.plain;
     let context = Arc::new({
         // we begin with an exclusive hold on the context; after leaving this block, we may not modify it
-        let mut context = RunContext::new(common_opts.clone(), cmd_opts.clone()).await?;
-        context.build_instance_list().await?;
+        if let Some(log_dir) = cmd_opts.resume.as_ref() {
+            let context = RunContext::resume(common_opts.clone(), log_dir).await?;
+            if context.jobs().is_empty() {
+                human_println!(
+                    context,
+                    "All instances of run {} have been processed. Done",
+                    context.run_uuid()
+                );
+                return Ok(());
+            }
+            context
+        } else {
+            let mut context = RunContext::new(common_opts.clone(), cmd_opts.clone()).await?;
+            context.build_instance_list().await?;
 
-        if context.instance_list().is_empty() {
-            anyhow::bail!("No instances to run");
-        }
-        if let Some(path) = cmd_opts.export_iid_only.as_ref() {
-            context.write_instance_list(path)?;
-            println!("Wrote instance list to {path:?}. Done");
-            return Ok(());
-        }
+            if context.instance_list().is_empty() {
+                anyhow::bail!("No instances to run");
+            }
+            if let Some(seed) = context.shuffle_seed() {
+                human_println!(
+                    context,
+                    "Shuffled instances with seed {seed}; pass `--shuffle-seed {seed}` to reproduce the order"
+                );
+            }
+            if let Some(path) = cmd_opts.export_iid_only.as_ref() {
+                context.write_instance_list(path)?;
+                println!("Wrote instance list to {path:?}. Done");
+                return Ok(());
+            }
 
-        context
+            context.store_run_info()?;
+            context
+        }
     });
 
+    if context.server_conn().is_offline() {
+        context.ensure_instances_cached().await?;
+    }
+
+    // when resuming, the options of the original run apply
+    let cmd_opts = context.cmd_opts();
+
     let avail_slots = cmd_opts.parallel_jobs;
     assert!(avail_slots > 0);
     let mut running_jobs: Vec<JobContext> = Vec::with_capacity(avail_slots);
-    let mut instances = context.instance_list();
+    let jobs = context.jobs();
+    let mut pending_jobs = jobs.as_slice();
+    let mut num_finished = 0;
 
-    let mut display = ProgressDisplay::new(context.clone())?;
+    context.events().emit(RunEvent::RunStarted {
+        run_uuid: context.run_uuid(),
+        jobs: jobs.len(),
+        solvers: context
+            .solvers()
+            .iter()
+            .map(|s| s.config.name.clone())
+            .collect(),
+    });
+
+    let mut display = new_display(context.clone(), plain)?;
     let mut report_error_on_exit = false;
 
-    let mut summary_logger =
-        RunSummaryLogger::try_new(&context.log_dir().join("summary.csv")).await?;
+    // one summary per solver
+    let mut summary_loggers = Vec::with_capacity(context.solvers().len());
+    for solver in 0..context.solvers().len() {
+        let path = context.summary_file(solver);
+        summary_loggers.push(if resumed {
+            RunSummaryLogger::try_append(&path).await?
+        } else {
+            RunSummaryLogger::try_new(&path).await?
+        });
+    }
+
+    // retry uploads that failed due to network problems in the background;
+    // in offline mode, uploads are only queued
+    let upload_retry = (!cmd_opts.no_upload && !context.server_conn().is_offline()).then(|| {
+        let context = context.clone();
+        tokio::spawn(async move {
+            context
+                .upload_queue()
+                .retry_with_backoff(context.server_conn(), context.solution_cache())
+                .await
+        })
+    });
+
+    let ctrl_c_count = count_ctrl_c()?;
+    let mut interruption = Interruption::None;
+
+    while !(pending_jobs.is_empty() && running_jobs.is_empty()) {
+        // first Ctrl-C: stop scheduling; second Ctrl-C: also stop running solvers;
+        // third Ctrl-C: kill running solvers and leave without waiting for them
+        match (interruption, ctrl_c_count.load(Ordering::Relaxed)) {
+            (Interruption::None, 1..) => {
+                interruption = Interruption::StopScheduling {
+                    not_started: pending_jobs.len(),
+                };
+                pending_jobs = &[];
+                display.set_notice(
+                    "Ctrl-C: no new jobs are started; waiting for running jobs to finish. Press Ctrl-C again to stop them.",
+                );
+            }
+            (Interruption::StopScheduling { not_started }, 2..) => {
+                interruption = Interruption::StopSolvers { not_started };
+                context.interrupt_solvers();
+                display.set_notice(
+                    "Ctrl-C: sent SIGTERM to all running solvers; waiting for grace period. Press Ctrl-C again to kill them and exit immediately.",
+                );
+            }
+            (Interruption::StopSolvers { .. }, 3..) => {
+                context.process_groups().kill_all();
+                anyhow::bail!("Run was aborted by Ctrl-C; all running solvers were killed");
+            }
+            _ => {}
+        }
 
-    while !(instances.is_empty() && running_jobs.is_empty()) {
         // attempt to spawn new tasks if there are available slots
         if avail_slots > running_jobs.len() {
-            if let Some((iid, rest)) = instances.split_first() {
-                instances = rest;
-                running_jobs.push(JobContext::new(context.clone(), *iid));
+            if let Some((spec, rest)) = pending_jobs.split_first() {
+                pending_jobs = rest;
+                running_jobs.push(JobContext::new(context.clone(), *spec));
+                display.start_job(*spec);
             }
         }
 
         // poll all running tasks to see if they are finished
         // need for-loop rather than `running_jobs.drain(..)` as poll is fallible async fn
         for job_context in running_jobs.iter_mut() {
-            let success = job_context.poll(&mut display, &mut summary_logger).await?;
+            let success = job_context
+                .poll(display.as_mut(), &mut summary_loggers)
+                .await?;
             report_error_on_exit |= success == Poll::Ready(JobSuccess::ReportAsFailure);
+            num_finished += success.is_ready() as usize;
         }
 
         // remove finished tasks from list
         running_jobs.retain_mut(|job| !job.is_finished());
 
-        display.tick(running_jobs.len());
+        let mut running = vec![0; context.solvers().len()];
+        for job_context in &running_jobs {
+            running[job_context.job.solver()] += 1;
+        }
+        display.tick(&running);
         let wait_for = if avail_slots > running_jobs.len() {
             SHORT_WAIT_TIME
         } else {
@@ -78,7 +189,56 @@ pub async fn command_run(common_opts: &CommonOpts, cmd_opts: &RunOpts) -> anyhow
         tokio::time::sleep(wait_for).await;
     }
 
+    if let Interruption::StopScheduling { not_started }
+    | Interruption::StopSolvers { not_started } = interruption
+    {
+        display.set_notice(&format!(
+            "Run interrupted by Ctrl-C; {not_started} jobs were not started. Continue with `run --resume {}`",
+            context.log_dir().display()
+        ));
+    }
+
+    // the retry task has to be gone before the queue is flushed; otherwise, both may send
+    // the same upload
+    let retry_uploads = upload_retry.is_some();
+    if let Some(task) = upload_retry {
+        task.abort();
+        let _ = task.await;
+    }
+
+    // no last attempt after Ctrl-C, since the user wants to leave quickly
+    let pending_uploads = match (retry_uploads, interruption) {
+        (true, Interruption::None) => flush_upload_queue(&context).await?,
+        (false, _) if cmd_opts.no_upload => 0,
+        _ => context.upload_queue().len().await?,
+    };
+
+    context.events().emit(RunEvent::RunFinished {
+        run_uuid: context.run_uuid(),
+        finished: num_finished,
+        not_started: match interruption {
+            Interruption::None => 0,
+            Interruption::StopScheduling { not_started }
+            | Interruption::StopSolvers { not_started } => not_started,
+        },
+        interrupted: interruption != Interruption::None,
+        pending_uploads,
+    });
+
     display.final_message();
+    if pending_uploads > 0 {
+        human_println!(
+            context,
+            "{}",
+            console::Style::new().yellow().apply_to(format!(
+                "{pending_uploads} uploads could not be sent; they are kept in the queue. Send them later using `flush-uploads`"
+            ))
+        );
+    }
+
+    if interruption != Interruption::None {
+        anyhow::bail!("Run was interrupted");
+    }
     if report_error_on_exit {
         anyhow::bail!("Some runs failed");
     }
@@ -86,6 +246,42 @@ pub async fn command_run(common_opts: &CommonOpts, cmd_opts: &RunOpts) -> anyhow
     Ok(())
 }
 
+/// Returns the number of uploads still in the queue
+async fn flush_upload_queue(context: &RunContext) -> anyhow::Result<usize> {
+    let queue = context.upload_queue();
+    if queue.is_empty().await? {
+        return Ok(0);
+    }
+
+    Ok(queue
+        .flush(context.server_conn(), context.solution_cache())
+        .await?
+        .remaining)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interruption {
+    None,
+    StopScheduling { not_started: usize },
+    StopSolvers { not_started: usize },
+}
+
+/// Counts the number of SIGINTs (Ctrl-C) received; installing the handler prevents
+/// SIGINT from terminating the runner
+fn count_ctrl_c() -> anyhow::Result<Arc<AtomicUsize>> {
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let task_counter = counter.clone();
+    let mut sigint = signal(SignalKind::interrupt())?;
+    tokio::spawn(async move {
+        while sigint.recv().await.is_some() {
+            task_counter.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    Ok(counter)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum JobSuccess {
     ReportAsFailure,
@@ -96,38 +292,33 @@ struct JobContext {
     run: Arc<RunContext>,
     job: Arc<Job>,
     task_handle: Option<tokio::task::JoinHandle<Result<JobResult, anyhow::Error>>>,
-    progress_bar: RunnerProgressBar,
     is_finished: bool,
 }
 
 impl JobContext {
-    fn new(run: Arc<RunContext>, iid: IId) -> Self {
-        let job = Arc::new(Job::new(run.clone(), iid));
+    fn new(run: Arc<RunContext>, spec: JobSpec) -> Self {
+        let job = Arc::new(Job::new(run.clone(), spec));
 
         let task_handle = {
             let job_task = job.clone();
             tokio::spawn(async move { job_task.main().await })
         };
 
-        let progress_bar = RunnerProgressBar::new(run.clone(), iid);
-
         Self {
             run,
             job,
             task_handle: Some(task_handle),
-            progress_bar,
             is_finished: false,
         }
     }
 
     async fn poll(
         &mut self,
-        display: &mut ProgressDisplay,
-        run_logger: &mut RunSummaryLogger,
+        display: &mut dyn RunDisplay,
+        run_loggers: &mut [RunSummaryLogger],
     ) -> anyhow::Result<Poll<JobSuccess>> {
         if !self.task_handle.as_ref().unwrap().is_finished() {
-            self.progress_bar
-                .update_progress_bar(display, &self.job, Instant::now());
+            display.update_job(&self.job, Instant::now());
 
             return Ok(Poll::Pending);
         }
@@ -138,15 +329,29 @@ impl JobContext {
             JobResultState::BestKnown { .. } => JobSuccess::ReportAsSuccess, // found solution
             JobResultState::Incomplete => JobSuccess::ReportAsSuccess, // good kind of lack of success
             JobResultState::Timeout => JobSuccess::ReportAsSuccess, // good kind of lack of success
+            JobResultState::MemoryLimitExceeded => JobSuccess::ReportAsSuccess, // same as timeout
+            JobResultState::Interrupted => JobSuccess::ReportAsSuccess, // user asked for it
             JobResultState::Suboptimal { .. } if !self.run.cmd_opts().suboptimal_is_error => {
                 JobSuccess::ReportAsSuccess
             }
             _ => JobSuccess::ReportAsFailure,
         };
 
-        run_logger.log_job_result(self.job.iid(), &result).await?;
+        self.run.events().emit(RunEvent::JobFinished {
+            job: self.run.job_ref(&self.job.spec()),
+            result: (&result).into(),
+        });
+
+        run_loggers[self.job.solver()]
+            .log_job_result(
+                self.job.iid(),
+                self.job.repetition(),
+                self.job.seed(),
+                &result,
+            )
+            .await?;
 
-        self.progress_bar.finish(display, result.state);
+        display.finish_job(self.job.spec(), &result);
         self.is_finished = true;
 
         Ok(Poll::Ready(report_error_on_exit))
@@ -1,65 +1,53 @@
-use std::collections::HashSet;
-use std::{io::BufRead, path::Path};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
+use anyhow::Context;
 use chrono::{DateTime, Local};
-use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
 use tracing::debug;
 use uuid::Uuid;
 
 use crate::utils::directory::StrideDirectory;
+use crate::utils::event_logger::{EventLogger, JobRef};
 use crate::utils::instance_data_db::InstanceDataDB;
-use crate::utils::meta_data_db::{DangerousRawClause, MetaDataDB};
+use crate::utils::meta_data_db::MetaDataDB;
+use crate::utils::run_summary_logger::{RunSummaryEntry, RunSummaryLogger};
 use crate::utils::server_connection::ServerConnection;
-use crate::utils::IId;
-
-use super::super::arguments::{CommonOpts, RunOpts};
-
-/// Reads a newline separated list of instance IDs from a file.
-/// Whitespaces are trimmed from the beginning and end of each line.
-/// Lines starting with 'c' are considered comments and ignored.
-fn read_instance_list(path: &Path) -> anyhow::Result<Vec<IId>> {
-    let file = std::fs::File::open(path)?;
-    let reader = std::io::BufReader::new(file);
-
-    let mut instances = Vec::new();
-    for org_line in reader.lines() {
-        let org_line = org_line?;
-        let line = org_line.trim();
-
-        if line.is_empty() || line.starts_with("c") {
-            continue;
-        }
-
-        let id = line.parse::<IId>()?;
-        instances.push(id);
-    }
-
-    Ok(instances)
-}
-
-async fn check_that_instances_exist(db: &MetaDataDB, instances: &[IId]) -> anyhow::Result<()> {
-    let all_known: HashSet<IId> = db
-        .fetch_instance_iids_from_db(DangerousRawClause("1=1"))
-        .await?
-        .into_iter()
-        .collect();
-    let proposed: HashSet<IId> = instances.iter().cloned().collect();
-
-    if !proposed.is_subset(&all_known) {
-        let mut difference: Vec<_> = proposed.difference(&all_known).collect();
-        difference.sort();
-        let len = difference.len();
-        let diff_str: Vec<_> = difference
-            .into_iter()
-            .take(20)
-            .map(|iid| iid.iid_to_u32().to_string())
-            .collect();
-        let diff_str = diff_str.join(", ");
-
-        anyhow::bail!("List contains {len} instance ids not found in metadata.db; try run `stride-runner update`. At least following IDs were not found {diff_str}");
-    }
-
-    Ok(())
+use crate::utils::solution_cache_db::SolutionCacheDB;
+use crate::utils::solver_executor::ProcessGroups;
+use crate::utils::upload_queue::UploadQueue;
+use crate::utils::{DId, IId};
+
+use super::super::arguments::{CommonOpts, InstanceOrder, RunOpts};
+use super::super::common::{
+    read_instance_list, resolve_summary_path, select_instances, summary_file_of_log_dir,
+};
+use super::ordering;
+use super::schedule::{schedule_jobs, JobSpec};
+use super::solver_config::{read_solver_configs, SolverConfig, SolverRun};
+use super::timeout_policy::TimeoutPolicy;
+
+const PATH_RUN_INFO: &str = "run.json";
+const PATH_INSTANCE_LIST: &str = "instances.list";
+
+/// Stored in the log directory to allow resuming an interrupted run
+#[derive(Serialize, Deserialize)]
+struct RunInfo {
+    run_uuid: Uuid,
+    cmd_opts: RunOpts,
+    /// Only set in multi-solver mode (`--solvers`)
+    #[serde(default)]
+    solvers: Vec<SolverRun>,
+    /// Seed of the first repetition; the i-th repetition uses `base_seed + i`
+    #[serde(default)]
+    base_seed: u64,
+    /// Stored, since the timeout file may have changed when resuming
+    #[serde(default)]
+    timeout_policy: Option<TimeoutPolicy>,
+    /// Seed used to shuffle the instances (`--order shuffle`); allows reproducing the order
+    #[serde(default)]
+    shuffle_seed: Option<u64>,
 }
 
 pub struct RunContext {
@@ -68,28 +56,191 @@ pub struct RunContext {
 
     start: DateTime<Local>,
     run_uuid: Uuid,
+    base_seed: u64,
+    timeout_policy: TimeoutPolicy,
+    shuffle_seed: Option<u64>,
 
     meta_data_db: MetaDataDB,
 
     instance_data_db: InstanceDataDB,
+    solution_cache: SolutionCacheDB,
+    upload_queue: UploadQueue,
     server_conn: ServerConnection,
+    events: EventLogger,
 
+    solvers: Vec<SolverRun>,
     instances: Vec<IId>,
+    /// Per solver, the (IID, repetition) pairs already processed before resuming
+    finished: Vec<HashSet<(IId, u32)>>,
+    /// Per solver, the results logged before resuming (except for interrupted jobs)
+    previous_results: Vec<Vec<RunSummaryEntry>>,
 
     log_dir: std::path::PathBuf,
+
+    interrupt_solvers: watch::Sender<bool>,
+    process_groups: ProcessGroups,
 }
 
 impl RunContext {
     pub async fn new(common_opts: CommonOpts, cmd_opts: RunOpts) -> anyhow::Result<Self> {
+        if cmd_opts.repetitions == 0 {
+            anyhow::bail!("--repetitions must be at least 1");
+        }
+
+        let start = chrono::Local::now();
+        let run_uuid = Uuid::new_v4();
+        let log_dir = Self::prepare_logdir(&common_opts, start, &run_uuid)?;
+
+        let solvers = match &cmd_opts.solvers {
+            Some(path) => {
+                if !cmd_opts.solver_args.is_empty() {
+                    anyhow::bail!("Solver arguments cannot be combined with --solvers; add them to the solver configurations instead");
+                }
+
+                read_solver_configs(path)?
+                    .into_iter()
+                    .map(|config| SolverRun {
+                        config,
+                        run_uuid: Uuid::new_v4(),
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let timeout_policy = TimeoutPolicy::try_from_opts(&cmd_opts)?;
+
+        let mut context = Self::new_with_run(
+            common_opts,
+            cmd_opts,
+            start,
+            run_uuid,
+            rand::random(),
+            solvers,
+            log_dir,
+        )
+        .await?;
+
+        if let Some(path) = &context.cmd_opts.json_events {
+            context.events = EventLogger::try_new(path, false)?;
+        }
+        context.timeout_policy = timeout_policy;
+
+        Ok(context)
+    }
+
+    /// Restores the context of an interrupted run from its log directory. Only jobs not yet
+    /// recorded in the summary of their solver are scheduled.
+    pub async fn resume(common_opts: CommonOpts, log_dir: &Path) -> anyhow::Result<Self> {
+        let info_path = log_dir.join(PATH_RUN_INFO);
+        let file = std::fs::File::open(&info_path).with_context(|| {
+            format!("Reading {info_path:?}; only runs started by this version can be resumed")
+        })?;
+        let RunInfo {
+            run_uuid,
+            cmd_opts,
+            solvers,
+            base_seed,
+            timeout_policy,
+            shuffle_seed,
+        } = serde_json::from_reader(file).with_context(|| format!("Parsing {info_path:?}"))?;
+
+        let mut context = Self::new_with_run(
+            common_opts,
+            cmd_opts,
+            chrono::Local::now(),
+            run_uuid,
+            base_seed,
+            solvers,
+            log_dir.to_path_buf(),
+        )
+        .await?;
+
+        // the events of the resumed part are appended to the original file
+        if let Some(path) = &context.cmd_opts.json_events {
+            context.events = EventLogger::try_new(path, true)?;
+        }
+
+        context.shuffle_seed = shuffle_seed;
+
+        // runs started before timeout policies were stored only used a fixed timeout
+        if let Some(timeout_policy) = timeout_policy {
+            context.timeout_policy = timeout_policy;
+        }
+
+        for solver in &context.solvers {
+            if !solver.config.binary.is_file() {
+                anyhow::bail!("Solver binary {:?} not found", solver.config.binary);
+            }
+        }
+
+        context.instances = read_instance_list(&log_dir.join(PATH_INSTANCE_LIST))?;
+        context.previous_results = (0..context.solvers.len())
+            .map(|solver| {
+                let summary_path = context.summary_file(solver);
+                if !summary_path.is_file() {
+                    return Ok(Vec::new());
+                }
+
+                let mut entries = RunSummaryLogger::read_entries(&summary_path)?;
+                entries.retain(|e| e.state != "interrupted");
+                debug!(
+                    "Skip {} jobs already processed by solver {}",
+                    entries.len(),
+                    solver
+                );
+                Ok(entries)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        context.finished = context
+            .previous_results
+            .iter()
+            .map(|entries| entries.iter().map(|e| (e.iid, e.repetition)).collect())
+            .collect();
+
+        Ok(context)
+    }
+
+    async fn new_with_run(
+        common_opts: CommonOpts,
+        cmd_opts: RunOpts,
+        start: DateTime<Local>,
+        run_uuid: Uuid,
+        base_seed: u64,
+        mut solvers: Vec<SolverRun>,
+        log_dir: std::path::PathBuf,
+    ) -> anyhow::Result<Self> {
+        if solvers.is_empty() {
+            // single-solver mode: the solver shares the Run UUID and log directory with the run
+            solvers.push(SolverRun {
+                config: SolverConfig {
+                    name: "solver".into(),
+                    binary: cmd_opts.solver_binary.clone(),
+                    args: cmd_opts.solver_args.clone(),
+                    solver_uuid: cmd_opts.solver_uuid,
+                },
+                run_uuid,
+            });
+        } else {
+            for solver in &solvers {
+                let dir = log_dir.join(&solver.config.name);
+                std::fs::create_dir_all(&dir).with_context(|| {
+                    format!(
+                        "Creating log directory {dir:?} of solver {}",
+                        solver.config.name
+                    )
+                })?;
+            }
+        }
+
         let stride_dir = StrideDirectory::try_default()?;
         let server_conn = ServerConnection::new_from_opts(&common_opts)?;
+        let timeout_policy = TimeoutPolicy::fixed(cmd_opts.timeout);
 
         let instance_data_db = InstanceDataDB::new(stride_dir.db_instance_file().as_path()).await?;
         let meta_data_db = MetaDataDB::new(stride_dir.db_meta_file().as_path()).await?;
-
-        let start = chrono::Local::now();
-        let run_uuid = Uuid::new_v4();
-        let log_dir = Self::prepare_logdir(&common_opts, start, &run_uuid)?;
+        let solution_cache = SolutionCacheDB::new(stride_dir.db_cache_file().as_path()).await?;
+        let upload_queue = UploadQueue::new(stride_dir.db_upload_queue_file().as_path()).await?;
 
         Ok(Self {
             common_opts,
@@ -97,14 +248,26 @@ impl RunContext {
 
             start,
             run_uuid,
+            base_seed,
+            timeout_policy,
+            shuffle_seed: None,
 
             meta_data_db,
             instance_data_db,
+            solution_cache,
+            upload_queue,
 
             server_conn,
+            events: EventLogger::disabled(),
+            solvers,
             instances: Vec::new(),
+            finished: Vec::new(),
+            previous_results: Vec::new(),
 
             log_dir,
+
+            interrupt_solvers: watch::Sender::new(false),
+            process_groups: ProcessGroups::default(),
         })
     }
 
@@ -140,6 +303,82 @@ impl RunContext {
         &self.instances
     }
 
+    /// Results of `solver` logged before the run was resumed; empty for a new run
+    pub fn previous_results(&self, solver: usize) -> &[RunSummaryEntry] {
+        self.previous_results
+            .get(solver)
+            .map_or(&[], |entries| entries.as_slice())
+    }
+
+    /// All jobs still to be processed in the order they are scheduled
+    pub fn jobs(&self) -> Vec<JobSpec> {
+        schedule_jobs(
+            &self.instances,
+            self.solvers.len(),
+            self.cmd_opts.repetitions,
+            &self.finished,
+        )
+    }
+
+    /// In offline mode, instances cannot be downloaded once the run started; hence we
+    /// report all instances of pending jobs that are not cached locally beforehand
+    pub async fn ensure_instances_cached(&self) -> anyhow::Result<()> {
+        let dids: HashMap<IId, DId> = self
+            .meta_data_db
+            .fetch_all_instances()
+            .await?
+            .into_iter()
+            .map(|instance| (instance.iid, instance.data_did))
+            .collect();
+        let cached = self.instance_data_db.fetch_cached_dids().await?;
+
+        let mut missing: Vec<IId> = self
+            .jobs()
+            .into_iter()
+            .map(|job| job.iid)
+            .filter(|iid| !dids.get(iid).is_some_and(|did| cached.contains(did)))
+            .collect();
+        missing.sort();
+        missing.dedup();
+
+        if !missing.is_empty() {
+            let iids: Vec<_> = missing
+                .iter()
+                .map(|iid| iid.iid_to_u32().to_string())
+                .collect();
+            anyhow::bail!(
+                "{} instances are not cached locally and cannot be downloaded in offline mode; fetch them using `prefetch` first. Missing IIDs: {}",
+                missing.len(),
+                iids.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Seed passed to the solver via `STRIDE_SEED`; all solvers and instances share the
+    /// seed of a repetition, while the repetitions of an instance get distinct seeds
+    pub fn seed(&self, repetition: u32) -> u64 {
+        self.base_seed.wrapping_add(repetition as u64)
+    }
+
+    pub fn timeout_policy(&self) -> &TimeoutPolicy {
+        &self.timeout_policy
+    }
+
+    /// Multiple solver configurations are run against each other (`--solvers`)
+    pub fn is_benchmark(&self) -> bool {
+        self.cmd_opts.solvers.is_some()
+    }
+
+    pub fn solvers(&self) -> &[SolverRun] {
+        &self.solvers
+    }
+
+    pub fn solver(&self, solver: usize) -> &SolverRun {
+        &self.solvers[solver]
+    }
+
     #[allow(dead_code)]
     pub fn start(&self) -> DateTime<Local> {
         self.start
@@ -157,59 +396,149 @@ impl RunContext {
         &self.instance_data_db
     }
 
+    pub fn solution_cache(&self) -> &SolutionCacheDB {
+        &self.solution_cache
+    }
+
+    pub fn upload_queue(&self) -> &UploadQueue {
+        &self.upload_queue
+    }
+
     pub fn log_dir(&self) -> &Path {
         &self.log_dir
     }
 
-    pub async fn build_instance_list(&mut self) -> anyhow::Result<()> {
-        if self.cmd_opts.instances.is_none() && self.cmd_opts.sql_where.is_none() {
-            anyhow::bail!("Must prove --instances and/or --sql-where");
+    pub fn events(&self) -> &EventLogger {
+        &self.events
+    }
+
+    /// Identifies the job in events; the solver is only named in multi-solver mode
+    pub fn job_ref(&self, spec: &JobSpec) -> JobRef {
+        JobRef::new(
+            spec.iid,
+            spec.repetition,
+            self.is_benchmark()
+                .then(|| self.solvers[spec.solver].config.name.as_str()),
+        )
+    }
+
+    /// If events are written to stdout, all other output goes to stderr to keep stdout parsable
+    pub fn events_to_stdout(&self) -> bool {
+        self.cmd_opts
+            .json_events
+            .as_ref()
+            .is_some_and(|path| path == Path::new("-"))
+    }
+
+    /// Receives `true` once all running solvers are asked to stop
+    pub fn interrupt_receiver(&self) -> watch::Receiver<bool> {
+        self.interrupt_solvers.subscribe()
+    }
+
+    /// Sends SIGTERM to all running (and future) solvers; they are given the usual grace period
+    pub fn interrupt_solvers(&self) {
+        self.interrupt_solvers.send_replace(true);
+    }
+
+    /// Process groups of all running solvers
+    pub fn process_groups(&self) -> &ProcessGroups {
+        &self.process_groups
+    }
+
+    /// In multi-solver mode, each solver logs into its own subdirectory named after it
+    pub fn solver_log_dir(&self, solver: usize) -> std::path::PathBuf {
+        if self.is_benchmark() {
+            self.log_dir.join(&self.solvers[solver].config.name)
+        } else {
+            self.log_dir.clone()
         }
+    }
 
-        let instances_from_file = match &self.cmd_opts.instances {
-            Some(path) => {
-                let instances = read_instance_list(path.as_path())?;
-                debug!("Read {} instances from {:?}", instances.len(), path);
-                check_that_instances_exist(self.meta_data_db(), &instances).await?;
-                Some(instances)
-            }
-            None => None,
+    pub fn summary_file(&self, solver: usize) -> std::path::PathBuf {
+        summary_file_of_log_dir(&self.solver_log_dir(solver))
+    }
+
+    /// Stores the options and the instance list in the log directory, so that the run can be resumed
+    pub fn store_run_info(&self) -> anyhow::Result<()> {
+        let file = std::fs::File::create(self.log_dir.join(PATH_RUN_INFO))?;
+        serde_json::to_writer_pretty(
+            file,
+            &RunInfo {
+                run_uuid: self.run_uuid,
+                cmd_opts: self.cmd_opts.clone(),
+                solvers: if self.is_benchmark() {
+                    self.solvers.clone()
+                } else {
+                    Vec::new()
+                },
+                base_seed: self.base_seed,
+                timeout_policy: Some(self.timeout_policy.clone()),
+                shuffle_seed: self.shuffle_seed,
+            },
+        )?;
+
+        self.write_instance_list(&self.log_dir.join(PATH_INSTANCE_LIST))
+    }
+
+    pub async fn build_instance_list(&mut self) -> anyhow::Result<()> {
+        let mut instances = select_instances(
+            self.meta_data_db(),
+            self.cmd_opts.instances.as_deref(),
+            self.cmd_opts.filter.as_deref(),
+            self.cmd_opts.sql_where.as_deref(),
+        )
+        .await?;
+
+        let order = if self.cmd_opts.sort_instances {
+            InstanceOrder::Iid
+        } else {
+            self.cmd_opts.order
         };
+        if order == InstanceOrder::Shuffle {
+            self.shuffle_seed = Some(self.cmd_opts.shuffle_seed.unwrap_or_else(rand::random));
+        }
 
-        let instances_from_db = match &self.cmd_opts.sql_where {
-            Some(where_clause) => {
-                let instances = self
-                    .meta_data_db()
-                    .fetch_instance_iids_from_db(DangerousRawClause(where_clause))
-                    .await?;
-                debug!(
-                    "Read {} instances from InstanceDB where {}",
-                    instances.len(),
-                    where_clause
+        self.order_instances(order, &mut instances).await?;
+        self.instances = instances;
+        Ok(())
+    }
+
+    /// Seed used to shuffle the instances; `None` if another order was chosen or the run was
+    /// started by an older version
+    pub fn shuffle_seed(&self) -> Option<u64> {
+        self.shuffle_seed
+    }
+
+    async fn order_instances(
+        &self,
+        order: InstanceOrder,
+        instances: &mut [IId],
+    ) -> anyhow::Result<()> {
+        match order {
+            InstanceOrder::Shuffle => {
+                let seed = self.shuffle_seed.expect("seed is chosen before shuffling");
+                debug!("Shuffle instances with seed {seed}");
+                ordering::shuffle(instances, seed);
+            }
+            InstanceOrder::Iid => instances.sort_unstable(),
+            InstanceOrder::SmallestFirst | InstanceOrder::LargestFirst => {
+                let sizes = self.meta_data_db.fetch_instance_sizes().await?;
+                ordering::sort_by_size(
+                    instances,
+                    &sizes,
+                    self.cmd_opts.size_by,
+                    order == InstanceOrder::LargestFirst,
                 );
-                Some(instances)
             }
-            None => None,
-        };
-
-        let mut instance = match (instances_from_file, instances_from_db) {
-            (Some(file), Some(db)) => {
-                let file: HashSet<_> = file.into_iter().collect();
-                let db: HashSet<_> = db.into_iter().collect();
-                file.intersection(&db).cloned().collect()
+            InstanceOrder::HardestFirst => {
+                let Some(path) = &self.cmd_opts.runtimes_from else {
+                    anyhow::bail!("--order hardest-first requires --runtimes-from");
+                };
+                let previous = RunSummaryLogger::read_entries(&resolve_summary_path(path)?)?;
+                ordering::sort_by_runtime(instances, &previous);
             }
-            (Some(file), None) => file,
-            (None, Some(db)) => db,
-            (None, None) => unreachable!(),
-        };
-
-        if self.cmd_opts.sort_instances {
-            instance.sort_unstable();
-        } else {
-            instance.shuffle(&mut rand::thread_rng());
         }
 
-        self.instances = instance;
         Ok(())
     }
 
@@ -234,33 +563,43 @@ impl RunContext {
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::io::Write;
-    use tempdir::TempDir;
-
-    const PREFIX: &str = "run-test";
 
     #[test]
-    fn read_instance_list() {
-        let tmp_dir = TempDir::new(PREFIX).unwrap();
-        let instances_file = tmp_dir.path().join("instances.txt");
-
-        // write some instances to the file
-        {
-            let mut file = std::fs::File::create(&instances_file).unwrap();
-            writeln!(file, "c comment").unwrap();
-            writeln!(file, " 1").unwrap();
-            writeln!(file).unwrap();
-            writeln!(file, "712 ").unwrap();
-            writeln!(file, " 4").unwrap();
-            writeln!(file, "  ").unwrap();
-            writeln!(file, "c comment").unwrap();
-            writeln!(file, "5").unwrap();
-        }
+    fn run_info_roundtrip() {
+        use structopt::StructOpt;
+
+        let mut cmd_opts =
+            RunOpts::from_iter_safe(["run", "-b", "solver", "-T", "42", "-w", "nodes < 10"])
+                .unwrap();
+        cmd_opts.solver_args = vec!["--foo".into()];
+
+        let info = RunInfo {
+            run_uuid: Uuid::new_v4(),
+            cmd_opts,
+            solvers: vec![SolverRun {
+                config: SolverConfig {
+                    name: "fast".into(),
+                    binary: "solver".into(),
+                    args: vec!["--fast".into()],
+                    solver_uuid: None,
+                },
+                run_uuid: Uuid::new_v4(),
+            }],
+            base_seed: 1234,
+            timeout_policy: Some(TimeoutPolicy::fixed(42)),
+            shuffle_seed: Some(5678),
+        };
 
-        let instances = super::read_instance_list(&instances_file).unwrap();
-        assert_eq!(
-            instances,
-            vec![IId::new(1), IId::new(712), IId::new(4), IId::new(5)]
-        );
+        let json = serde_json::to_string(&info).unwrap();
+        let read_back: RunInfo = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(read_back.run_uuid, info.run_uuid);
+        assert_eq!(read_back.cmd_opts.timeout, 42);
+        assert_eq!(read_back.cmd_opts.filter.as_deref(), Some("nodes < 10"));
+        assert_eq!(read_back.cmd_opts.solver_args, vec!["--foo".to_string()]);
+        assert_eq!(read_back.solvers, info.solvers);
+        assert_eq!(read_back.base_seed, 1234);
+        assert_eq!(read_back.timeout_policy, info.timeout_policy);
+        assert_eq!(read_back.shuffle_seed, Some(5678));
     }
 }
@@ -0,0 +1,230 @@
+use std::{collections::HashMap, sync::Arc};
+
+use console::Style;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use tokio::time::Instant;
+
+use super::{
+    super::{
+        context::RunContext,
+        job::{Job, JobResult, JobState},
+        schedule::JobSpec,
+    },
+    statistics::{job_label, RunStatistics},
+    RunDisplay,
+};
+
+/// Interactive display with one progress bar per running job
+pub struct ProgressDisplay {
+    context: Arc<RunContext>,
+    mpb: MultiProgress,
+    /// Never updated, but owned to keep the line alive
+    _link_line: ProgressBar,
+    /// One per solver
+    status_lines: Vec<ProgressBar>,
+    pb_total: ProgressBar,
+
+    statistics: RunStatistics,
+    bars: HashMap<JobSpec, RunnerProgressBar>,
+
+    notice_line: Option<ProgressBar>,
+}
+
+impl ProgressDisplay {
+    pub fn new(context: Arc<RunContext>) -> anyhow::Result<Self> {
+        let mpb = MultiProgress::new();
+        let statistics = RunStatistics::new(context.clone());
+
+        let link_line = mpb.add(ProgressBar::no_length());
+        link_line.set_style(ProgressStyle::default_bar().template("{msg}").unwrap());
+        link_line.set_message(statistics.link_message());
+
+        let status_lines: Vec<_> = context
+            .solvers()
+            .iter()
+            .map(|_| {
+                let line = mpb.add(ProgressBar::no_length());
+                line.set_style(ProgressStyle::default_bar().template("{msg}").unwrap());
+                line
+            })
+            .collect();
+
+        let pb_total = mpb.add(indicatif::ProgressBar::new(context.jobs().len() as u64));
+        pb_total.set_style(
+            ProgressStyle::default_bar()
+                .template("{msg:<15} [{elapsed_precise}] [{bar:50.green/grey}] {human_pos} of {human_len} (est: {eta})")?
+                .progress_chars("#>-"),
+        );
+
+        pb_total.set_message("Total finished");
+
+        Ok(Self {
+            context,
+            mpb,
+            _link_line: link_line,
+            status_lines,
+            pb_total,
+            statistics,
+            bars: HashMap::new(),
+
+            notice_line: None,
+        })
+    }
+}
+
+impl RunDisplay for ProgressDisplay {
+    fn start_job(&mut self, spec: JobSpec) {
+        self.bars
+            .insert(spec, RunnerProgressBar::new(self.context.clone(), spec));
+    }
+
+    fn update_job(&mut self, job: &Job, now: Instant) {
+        if let Some(bar) = self.bars.get_mut(&job.spec()) {
+            bar.update_progress_bar(&self.mpb, job, now);
+        }
+    }
+
+    fn finish_job(&mut self, spec: JobSpec, result: &JobResult) {
+        if let Some(pb) = self.bars.remove(&spec).and_then(|bar| bar.pb) {
+            self.mpb.remove(&pb);
+        }
+
+        self.pb_total.inc(1);
+        self.statistics.record(spec, &result.state);
+    }
+
+    fn tick(&mut self, running: &[usize]) {
+        for (line, message) in self
+            .status_lines
+            .iter()
+            .zip(self.statistics.status_messages(running))
+        {
+            line.set_message(message);
+        }
+    }
+
+    /// Shows the notice below the status lines
+    fn set_notice(&mut self, notice: &str) {
+        let line = self.notice_line.get_or_insert_with(|| {
+            let line = self
+                .mpb
+                .insert_after(self.status_lines.last().unwrap(), ProgressBar::no_length());
+            line.set_style(ProgressStyle::default_bar().template("{msg}").unwrap());
+            line
+        });
+
+        line.set_message(Style::new().yellow().bold().apply_to(notice).to_string());
+    }
+
+    fn final_message(&self) {
+        self.statistics.print_summary();
+        if let Some(line) = &self.notice_line {
+            human_println!(self.context, "{}", line.message());
+        }
+    }
+}
+
+struct RunnerProgressBar {
+    context: Arc<RunContext>,
+    spec: JobSpec,
+    pb: Option<ProgressBar>,
+    previous_state: Option<JobState>,
+    start: tokio::time::Instant,
+    /// Taken from the job once its timeout policy was resolved
+    timeout_millis: u64,
+}
+
+impl RunnerProgressBar {
+    const MILLIS_BEFORE_PROGRESS_BAR: u64 = 100;
+
+    fn new(context: Arc<RunContext>, spec: JobSpec) -> Self {
+        let timeout_millis = context.cmd_opts().timeout * 1000;
+        Self {
+            context,
+            spec,
+            start: tokio::time::Instant::now(),
+            timeout_millis,
+            pb: None,
+            previous_state: None,
+        }
+    }
+
+    fn max_time_millis(&self) -> u64 {
+        self.timeout_millis + self.context.cmd_opts().grace * 1000
+    }
+
+    fn update_progress_bar(&mut self, mpb: &MultiProgress, runner: &Job, now: Instant) {
+        if let Some(timeout_sec) = runner.timeout_sec() {
+            self.timeout_millis = timeout_sec * 1000;
+        }
+
+        let elapsed =
+            (now.duration_since(self.start).as_millis() as u64).min(self.max_time_millis());
+        if elapsed < Self::MILLIS_BEFORE_PROGRESS_BAR {
+            return; // do not create a progress bar for short running tasks
+        }
+
+        if self.pb.is_none() {
+            self.create_pb(mpb);
+        }
+
+        let pb = self.pb.as_ref().unwrap();
+
+        let runner_state = runner.state();
+        if Some(runner_state) != self.previous_state {
+            self.previous_state = Some(runner_state);
+            self.start = now;
+            self.pb.as_ref().unwrap().reset_elapsed();
+
+            if runner_state == JobState::Running {
+                self.style_for_running(pb);
+            } else {
+                self.style_for_waiting(pb);
+            }
+        }
+
+        let message: String = match runner.state() {
+            JobState::Idle => "startup".into(),
+            JobState::Fetching => "fetching data".into(),
+            JobState::Starting => "starting".into(),
+            JobState::Running => {
+                if elapsed > self.timeout_millis {
+                    Style::new().red().apply_to("grace").to_string()
+                } else {
+                    format!("running (timeout: {}s)", self.timeout_millis / 1000)
+                }
+            }
+            JobState::PostProcessing => "post-processing / upload".into(),
+            JobState::Finished => "done".into(),
+        };
+
+        pb.set_message(message);
+        pb.set_position(elapsed);
+    }
+
+    fn create_pb(&mut self, mpb: &MultiProgress) {
+        let pb = mpb.add(ProgressBar::new(self.max_time_millis()));
+        self.pb = Some(pb);
+    }
+
+    fn style_for_running(&self, pb: &ProgressBar) {
+        let mut template = job_label(&self.context, &self.spec);
+        template += "[{elapsed_precise}] [{bar:50.cyan/blue}] {msg}";
+
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template(&template)
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+
+        pb.set_length(self.max_time_millis());
+    }
+
+    fn style_for_waiting(&self, pb: &ProgressBar) {
+        let mut template = job_label(&self.context, &self.spec);
+        template += "[{elapsed_precise}] {spinner:.green}                                                    {msg}";
+
+        pb.set_style(ProgressStyle::default_bar().template(&template).unwrap());
+    }
+}
@@ -0,0 +1,56 @@
+use std::{io::IsTerminal, sync::Arc};
+
+use tokio::time::Instant;
+
+use super::{
+    context::RunContext,
+    job::{Job, JobResult},
+    schedule::JobSpec,
+};
+
+/// Like `println!`, but writes to stderr if events are written to stdout (`--json-events -`)
+macro_rules! human_println {
+    ($context:expr, $($arg:tt)*) => {
+        if $context.events_to_stdout() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+pub(super) use human_println;
+
+mod fancy;
+mod plain;
+mod statistics;
+
+pub use fancy::ProgressDisplay;
+pub use plain::PlainDisplay;
+
+/// Shows the progress of a run; `command_run` reports all jobs to it
+pub trait RunDisplay {
+    /// The job was scheduled
+    fn start_job(&mut self, spec: JobSpec);
+
+    /// Called regularly for each job that is not finished yet
+    fn update_job(&mut self, job: &Job, now: Instant);
+
+    fn finish_job(&mut self, spec: JobSpec, result: &JobResult);
+
+    /// Called regularly by the main loop; `running[i]` is the number of running jobs of solver `i`
+    fn tick(&mut self, running: &[usize]);
+
+    /// Shows a highlighted message (e.g., after Ctrl-C was pressed)
+    fn set_notice(&mut self, notice: &str);
+
+    fn final_message(&self);
+}
+
+/// Uses progress bars unless `plain` is set or stdout is not a terminal (e.g., in CI logs)
+pub fn new_display(context: Arc<RunContext>, plain: bool) -> anyhow::Result<Box<dyn RunDisplay>> {
+    if plain || !std::io::stdout().is_terminal() {
+        Ok(Box::new(PlainDisplay::new(context)))
+    } else {
+        Ok(Box::new(ProgressDisplay::new(context)?))
+    }
+}
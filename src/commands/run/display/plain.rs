@@ -0,0 +1,94 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::time::Instant;
+
+use super::{
+    super::{
+        context::RunContext,
+        job::{Job, JobResult, JobResultState},
+        schedule::JobSpec,
+    },
+    statistics::{job_label, RunStatistics},
+    RunDisplay,
+};
+
+/// Append-only display for non-interactive output (e.g., CI logs): one line per finished job
+/// and a periodic status line
+pub struct PlainDisplay {
+    context: Arc<RunContext>,
+    statistics: RunStatistics,
+    num_finished: usize,
+    num_jobs: usize,
+    last_status: Instant,
+}
+
+impl PlainDisplay {
+    const STATUS_INTERVAL: Duration = Duration::from_secs(30);
+
+    pub fn new(context: Arc<RunContext>) -> Self {
+        let display = Self {
+            statistics: RunStatistics::new(context.clone()),
+            num_finished: 0,
+            num_jobs: context.jobs().len(),
+            context,
+            last_status: Instant::now(),
+        };
+
+        human_println!(display.context, "{}", display.statistics.link_message());
+        display
+    }
+}
+
+impl RunDisplay for PlainDisplay {
+    fn start_job(&mut self, _spec: JobSpec) {}
+
+    fn update_job(&mut self, _job: &Job, _now: Instant) {}
+
+    fn finish_job(&mut self, spec: JobSpec, result: &JobResult) {
+        self.num_finished += 1;
+        self.statistics.record(spec, &result.state);
+
+        let format_score = |score: Option<u32>| score.map_or_else(|| "-".into(), |s| s.to_string());
+        let best_known = match result.state {
+            JobResultState::BestKnown { score } => Some(score),
+            JobResultState::Suboptimal { best_known, .. } => Some(best_known),
+            _ => None,
+        };
+
+        let mut line = format!(
+            "[{:>w$}/{}] {}| {:<13} | score: {:>7} | best known: {:>7} | {:>8.2}s",
+            self.num_finished,
+            self.num_jobs,
+            job_label(&self.context, &spec),
+            result.state.to_string(),
+            format_score(result.state.score()),
+            format_score(best_known),
+            result.runtime.as_secs_f64(),
+            w = self.num_jobs.to_string().len(),
+        );
+        if let Some(details) = result.state.details() {
+            line += &format!(" | {details}");
+        }
+
+        human_println!(self.context, "{line}");
+    }
+
+    fn tick(&mut self, running: &[usize]) {
+        if self.last_status.elapsed() < Self::STATUS_INTERVAL {
+            return;
+        }
+        self.last_status = Instant::now();
+
+        for line in self.statistics.status_messages(running) {
+            human_println!(self.context, "{line}");
+        }
+    }
+
+    fn set_notice(&mut self, notice: &str) {
+        human_println!(self.context, "{notice}");
+    }
+
+    fn final_message(&self) {
+        self.statistics.print_summary();
+    }
+}
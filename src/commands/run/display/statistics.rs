@@ -0,0 +1,365 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use console::{Attribute, Style};
+
+use crate::utils::IId;
+
+use super::super::{context::RunContext, job::JobResultState, schedule::JobSpec};
+
+/// Number of finished jobs per result state of a single solver
+#[derive(Debug, Clone, Default)]
+struct ResultCounters {
+    num_optimal: u64,
+    num_suboptimal: u64,
+    num_infeasible: u64,
+    num_syntax_error: u64,
+    num_crashed: u64,
+    num_exit_code: u64,
+    num_timeout: u64,
+    num_incomplete: u64,
+    num_memory_limit: u64,
+    num_interrupted: u64,
+}
+
+impl ResultCounters {
+    fn count(&mut self, status: &JobResultState) {
+        match status {
+            JobResultState::BestKnown { .. } => self.num_optimal += 1,
+            JobResultState::Suboptimal { .. } => self.num_suboptimal += 1,
+            JobResultState::Infeasible => self.num_infeasible += 1,
+            JobResultState::SyntaxError { .. } => self.num_syntax_error += 1,
+            JobResultState::Crashed { .. } => self.num_crashed += 1,
+            JobResultState::NonZeroExitCode { .. } => self.num_exit_code += 1,
+            JobResultState::Timeout => self.num_timeout += 1,
+            JobResultState::Incomplete => self.num_incomplete += 1,
+            JobResultState::MemoryLimitExceeded => self.num_memory_limit += 1,
+            JobResultState::Interrupted => self.num_interrupted += 1,
+        }
+    }
+
+    /// Name, value and highlighting (used if the value is non-zero) of each counter in display order
+    fn entries(&self, suboptimal_is_error: bool) -> [(&'static str, u64, Style); 10] {
+        const CRITICAL: [Attribute; 2] = [Attribute::Bold, Attribute::Underlined];
+        let critical = |style: Style| CRITICAL.into_iter().fold(style, |s, a| s.attr(a));
+
+        [
+            ("Opt", self.num_optimal, Style::new().green()),
+            (
+                "Subopt",
+                self.num_suboptimal,
+                if suboptimal_is_error {
+                    critical(Style::new().red())
+                } else {
+                    Style::new().blue()
+                },
+            ),
+            ("Incomp", self.num_incomplete, Style::new().yellow()),
+            ("Timeout", self.num_timeout, Style::new().yellow()),
+            ("MemLim", self.num_memory_limit, Style::new().yellow()),
+            ("Intr", self.num_interrupted, Style::new().yellow()),
+            ("Syntax", self.num_syntax_error, Style::new().red()),
+            ("Crash", self.num_crashed, Style::new().red()),
+            ("ExitCode", self.num_exit_code, Style::new().red()),
+            ("Infeas", self.num_infeasible, critical(Style::new().red())),
+        ]
+    }
+}
+
+fn highlight_non_zero(text: String, value: u64, style: &Style) -> String {
+    if value == 0 {
+        text
+    } else {
+        style.apply_to(text).to_string()
+    }
+}
+
+/// Aggregates the results of finished jobs; shared by all displays
+pub struct RunStatistics {
+    context: Arc<RunContext>,
+
+    /// One per solver
+    counters: Vec<ResultCounters>,
+
+    /// Scores of all repetitions per (IID, solver); only collected with `--repetitions` > 1.
+    /// When resuming, the repetitions finished before are included.
+    scores: BTreeMap<(IId, usize), Vec<Option<u32>>>,
+}
+
+impl RunStatistics {
+    pub fn new(context: Arc<RunContext>) -> Self {
+        let mut scores: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        if context.cmd_opts().repetitions > 1 {
+            for solver in 0..context.solvers().len() {
+                for entry in context.previous_results(solver) {
+                    scores
+                        .entry((entry.iid, solver))
+                        .or_default()
+                        .push(entry.score);
+                }
+            }
+        }
+
+        Self {
+            counters: vec![ResultCounters::default(); context.solvers().len()],
+            context,
+            scores,
+        }
+    }
+
+    pub fn record(&mut self, spec: JobSpec, status: &JobResultState) {
+        self.counters[spec.solver].count(status);
+
+        if self.context.cmd_opts().repetitions > 1 {
+            self.scores
+                .entry((spec.iid, spec.solver))
+                .or_default()
+                .push(status.score());
+        }
+    }
+
+    /// Upload mode and Run UUID
+    pub fn link_message(&self) -> String {
+        let context = &self.context;
+
+        if context.is_benchmark() {
+            let upload = if context.cmd_opts().no_upload {
+                Style::new().red().apply_to("upload disabled").to_string()
+            } else {
+                "results uploaded with one Run UUID per solver".into()
+            };
+
+            return format!(
+                "{upload} | Benchmark of {} solvers | Run: {}",
+                context.solvers().len(),
+                context.run_uuid()
+            );
+        }
+
+        match (
+            &context.cmd_opts().solver_uuid,
+            context.cmd_opts().no_upload,
+        ) {
+            (_, true) => {
+                format!(
+                    "{} | Run: {}",
+                    Style::new().red().apply_to("upload disabled"),
+                    context.run_uuid()
+                )
+            }
+            (Some(uuid), false) => {
+                let url = context
+                    .server_conn()
+                    .solver_website_for_user(*uuid)
+                    .to_string();
+
+                format!("visit {url} | Run: {}", context.run_uuid())
+            }
+            (_, false) => {
+                format!(
+                    "{} | Run: {}",
+                    Style::new()
+                        .yellow()
+                        .apply_to("consider to register solver for more stats"),
+                    context.run_uuid()
+                )
+            }
+        }
+    }
+
+    /// One line with all counters per solver; `running[i]` is the number of running jobs of solver `i`
+    pub fn status_messages(&self, running: &[usize]) -> Vec<String> {
+        let suboptimal_is_error = self.context.cmd_opts().suboptimal_is_error;
+        let name_width = solver_name_width(&self.context);
+
+        self.counters
+            .iter()
+            .enumerate()
+            .map(|(solver, counters)| {
+                let mut parts: Vec<_> = counters
+                    .entries(suboptimal_is_error)
+                    .into_iter()
+                    .map(|(name, value, style)| {
+                        highlight_non_zero(format!("{name}: {value:>6}"), value, &style)
+                    })
+                    .collect();
+                parts.push(format!("Running: {}", running[solver]));
+
+                let message = parts.join(" | ");
+                if self.context.is_benchmark() {
+                    let name = &self.context.solver(solver).config.name;
+                    format!("{name:<name_width$} | {message}")
+                } else {
+                    message
+                }
+            })
+            .collect()
+    }
+
+    /// Prints the link line, the counters, and (with repetitions) the score spread
+    pub fn print_summary(&self) {
+        human_println!(self.context, "{}", self.link_message());
+        if self.context.is_benchmark() {
+            self.print_comparison();
+        } else {
+            for line in self.status_messages(&vec![0; self.counters.len()]) {
+                human_println!(self.context, "{line}");
+            }
+        }
+        if !self.scores.is_empty() {
+            self.print_score_spread();
+        }
+    }
+
+    /// Prints the best, median and worst score over all repetitions of each instance
+    fn print_score_spread(&self) {
+        let name_width = if self.context.is_benchmark() {
+            solver_name_width(&self.context).max(6) + 1
+        } else {
+            0
+        };
+
+        human_println!(
+            self.context,
+            "{}",
+            Style::new().bold().apply_to(format!(
+                "{:>8} {:<name_width$}{:>8} {:>8} {:>8} {:>8}",
+                "IID",
+                if name_width > 0 { "Solver" } else { "" },
+                "Best",
+                "Median",
+                "Worst",
+                "Solved"
+            ))
+        );
+
+        for ((iid, solver), scores) in &self.scores {
+            let mut solved: Vec<u32> = scores.iter().flatten().copied().collect();
+            let (best, median, worst) = match best_median_worst(&mut solved) {
+                Some((best, median, worst)) => {
+                    (best.to_string(), format!("{median:.1}"), worst.to_string())
+                }
+                None => ("-".into(), "-".into(), "-".into()),
+            };
+
+            let name = if name_width > 0 {
+                self.context.solver(*solver).config.name.as_str()
+            } else {
+                ""
+            };
+
+            human_println!(
+                self.context,
+                "{:>8} {:<name_width$}{:>8} {:>8} {:>8} {:>8}",
+                iid.iid_to_u32(),
+                name,
+                best,
+                median,
+                worst,
+                format!("{}/{}", solved.len(), scores.len())
+            );
+        }
+    }
+
+    /// Prints the counters of all solvers side-by-side; one column per solver
+    fn print_comparison(&self) {
+        let suboptimal_is_error = self.context.cmd_opts().suboptimal_is_error;
+        let widths: Vec<_> = self
+            .context
+            .solvers()
+            .iter()
+            .map(|s| s.config.name.len().max(6))
+            .collect();
+
+        let header: Vec<_> = self
+            .context
+            .solvers()
+            .iter()
+            .zip(&widths)
+            .map(|(s, w)| format!("{:>w$}", s.config.name))
+            .collect();
+        human_println!(
+            self.context,
+            "{}",
+            Style::new()
+                .bold()
+                .apply_to(format!("{:<8} {}", "", header.join(" ")))
+        );
+
+        let entries: Vec<_> = self
+            .counters
+            .iter()
+            .map(|c| c.entries(suboptimal_is_error))
+            .collect();
+
+        for row in 0..entries[0].len() {
+            let cells: Vec<_> = entries
+                .iter()
+                .zip(&widths)
+                .map(|(solver, w)| {
+                    let (_, value, style) = &solver[row];
+                    highlight_non_zero(format!("{value:>w$}"), *value, style)
+                })
+                .collect();
+
+            human_println!(self.context, "{:<8} {}", entries[0][row].0, cells.join(" "));
+        }
+    }
+}
+
+/// Returns the smallest, median and largest score; the median of an even number of scores
+/// is the mean of the two middle ones
+fn best_median_worst(scores: &mut [u32]) -> Option<(u32, f64, u32)> {
+    if scores.is_empty() {
+        return None;
+    }
+
+    scores.sort_unstable();
+    let mid = scores.len() / 2;
+    let median = if scores.len() % 2 == 1 {
+        scores[mid] as f64
+    } else {
+        (scores[mid - 1] as f64 + scores[mid] as f64) / 2.0
+    };
+
+    Some((scores[0], median, *scores.last().unwrap()))
+}
+
+/// Length of the longest solver name; used to align per-solver lines
+fn solver_name_width(context: &RunContext) -> usize {
+    context
+        .solvers()
+        .iter()
+        .map(|s| s.config.name.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Identifies a job in the display. In multi-solver mode, the label also contains the solver's
+/// name; with repetitions, the (one-based) repetition is added
+pub fn job_label(context: &RunContext, spec: &JobSpec) -> String {
+    let mut label = format!("Inst. ID {: >6} ", spec.iid.iid_to_u32());
+    let repetitions = context.cmd_opts().repetitions;
+    if repetitions > 1 {
+        let width = repetitions.to_string().len();
+        label += &format!("#{:<width$} ", spec.repetition + 1);
+    }
+    if context.is_benchmark() {
+        let width = solver_name_width(context);
+        label += &format!("{:<width$} ", context.solver(spec.solver).config.name);
+    }
+    label
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn best_median_worst() {
+        assert_eq!(super::best_median_worst(&mut []), None);
+        assert_eq!(super::best_median_worst(&mut [7]), Some((7, 7.0, 7)));
+        assert_eq!(super::best_median_worst(&mut [9, 3, 5]), Some((3, 5.0, 9)));
+        assert_eq!(
+            super::best_median_worst(&mut [4, 10, 3, 5]),
+            Some((3, 4.5, 10))
+        );
+    }
+}
@@ -2,30 +2,51 @@ use std::{
     fmt::Display,
     sync::{
         atomic::{AtomicU8, Ordering},
-        Arc,
+        Arc, OnceLock,
     },
 };
 
+use serde::Serialize;
 use std::time::Duration;
-use tracing::trace;
+use tracing::{debug, trace, warn};
 
+use crate::pace::Solution;
 use crate::utils::{
     meta_data_db::InstanceModel,
-    solution_upload::{is_score_good_enough_for_upload, SolutionUploadRequestBuilder},
-    solver_executor::{SolverExecutorBuilder, SolverResult},
+    solution_upload::{
+        is_score_good_enough_for_upload, send_upload_request, SolutionUploadRequestBuilder,
+        UploadStatus,
+    },
+    solver_executor::{ResourceUsage, SolverExecutorBuilder, SolverResult},
     IId,
 };
 
-use super::context::RunContext;
+use super::{context::RunContext, schedule::JobSpec, solver_config::SolverRun};
+use crate::utils::event_logger::RunEvent;
 
+/// What became of a job's result after trying to upload it
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UploadOutcome {
+    /// Accepted by the server
+    Sent,
+    /// Server not reachable; pushed to the upload queue
+    Queued,
+    /// Not uploaded on purpose or rejected by the server
+    NotUploaded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JobResultState {
     BestKnown { score: u32 },
     Suboptimal { score: u32, best_known: u32 },
     Infeasible,
     Incomplete,
-    Error,
+    SyntaxError { message: String },
+    Crashed { signal: i32 },
+    NonZeroExitCode { code: i32 },
     Timeout,
+    MemoryLimitExceeded,
+    Interrupted,
 }
 
 impl Display for JobResultState {
@@ -35,8 +56,12 @@ impl Display for JobResultState {
             Self::Suboptimal { .. } => "suboptimal",
             Self::Infeasible => "infeasible",
             Self::Incomplete => "incomplete",
-            Self::Error => "error",
+            Self::SyntaxError { .. } => "syntax_error",
+            Self::Crashed { .. } => "crashed",
+            Self::NonZeroExitCode { .. } => "exit_code",
             Self::Timeout => "timeout",
+            Self::MemoryLimitExceeded => "memory_limit",
+            Self::Interrupted => "interrupted",
         })
     }
 }
@@ -49,14 +74,53 @@ impl JobResultState {
     pub fn is_suboptimal(&self) -> bool {
         matches!(self, Self::Suboptimal { .. })
     }
+
+    /// Size of the feasible solution, if the solver produced one
+    pub fn score(&self) -> Option<u32> {
+        match self {
+            Self::BestKnown { score } | Self::Suboptimal { score, .. } => Some(*score),
+            _ => None,
+        }
+    }
+
+    /// Additional information on why the solver failed, e.g., the signal it crashed with
+    pub fn details(&self) -> Option<String> {
+        match self {
+            Self::SyntaxError { message } => Some(message.clone()),
+            Self::Crashed { signal } => Some(format!("{} ({signal})", signal_name(*signal))),
+            Self::NonZeroExitCode { code } => Some(code.to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGILL => "SIGILL",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGXCPU => "SIGXCPU",
+        _ => "signal",
+    }
 }
 
 pub struct JobResult {
     pub state: JobResultState,
     pub runtime: Duration,
+    pub resource_usage: Option<ResourceUsage>,
+    /// The solver timed out and was scored by its best intermediate solution (`--anytime`)
+    pub from_intermediate: bool,
+    /// The solver was stopped early after this time, since it reached the best known score (`--stop-at-best`)
+    pub target_reached: Option<Duration>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum JobState {
     Idle = 0,
     Fetching = 1,
@@ -96,8 +160,12 @@ impl AtomicJobState {
 
 pub struct Job {
     context: Arc<RunContext>,
+    solver: usize,
     iid: IId,
+    repetition: u32,
     state: AtomicJobState,
+    /// Resolved by the timeout policy once the instance's metadata is known
+    timeout_sec: OnceLock<u64>,
 }
 
 fn instance_to_env(inst: &InstanceModel) -> Vec<(String, String)> {
@@ -135,15 +203,22 @@ fn instance_to_env(inst: &InstanceModel) -> Vec<(String, String)> {
 }
 
 impl Job {
-    pub fn new(context: Arc<RunContext>, iid: IId) -> Self {
+    pub fn new(context: Arc<RunContext>, spec: JobSpec) -> Self {
         Self {
             context,
-            iid,
+            solver: spec.solver,
+            iid: spec.iid,
+            repetition: spec.repetition,
             state: AtomicJobState::new(JobState::Idle),
+            timeout_sec: OnceLock::new(),
         }
     }
 
     pub async fn main(&self) -> anyhow::Result<JobResult> {
+        self.context.events().emit(RunEvent::JobStarted {
+            job: self.context.job_ref(&self.spec()),
+        });
+
         self.update_state(JobState::Fetching);
         let meta = self.context.meta_data_db().fetch_instance(self.iid).await?;
         let mut data = self
@@ -160,15 +235,27 @@ impl Job {
                 .join("\n");
         }
 
+        let timeout_sec = *self
+            .timeout_sec
+            .get_or_init(|| self.context.timeout_policy().timeout_for(&meta));
+
         self.update_state(JobState::Starting);
-        let env = self.prepare_env_variables(&meta);
+        let env = self.prepare_env_variables(&meta, timeout_sec);
 
+        let cmd_opts = self.context.cmd_opts();
         let mut executor = SolverExecutorBuilder::default()
-            .solver_path(self.context.cmd_opts().solver_binary.clone())
-            .working_dir(self.context.log_dir().to_path_buf())
-            .args(self.context.cmd_opts().solver_args.clone())
-            .timeout(self.context.cmd_opts().timeout_duration())
-            .grace(self.context.cmd_opts().grace_duration())
+            .solver_path(self.solver_run().config.binary.clone())
+            .working_dir(self.context.solver_log_dir(self.solver))
+            .args(self.solver_run().config.args.clone())
+            .timeout(Duration::from_secs(timeout_sec))
+            .grace(cmd_opts.grace_duration())
+            .memory_limit_mb(cmd_opts.memory_limit)
+            .cpu_time_limit(cmd_opts.cpu_limit_duration())
+            .interrupt(self.context.interrupt_receiver())
+            .process_groups(self.context.process_groups().clone())
+            .track_intermediate(cmd_opts.anytime || cmd_opts.stop_at_best)
+            .anytime(cmd_opts.anytime)
+            .target_score(meta.best_score.filter(|_| cmd_opts.stop_at_best))
             .instance_id(self.iid)
             .instance_data(data)
             .env(env)
@@ -181,9 +268,73 @@ impl Job {
         self.update_state(JobState::PostProcessing);
 
         let runtime = executor.runtime().unwrap();
+        // a result scored by an intermediate solution was found when the solution was written,
+        // not when the solver was stopped
+        let upload_runtime = executor.intermediate_elapsed().unwrap_or(runtime);
+        let resource_usage = executor.resource_usage();
+
+        let solution = match &result {
+            SolverResult::Valid { data } => Some(Solution::from_1indexed_vec(data.clone(), None)?),
+            _ => None,
+        };
+
+        let already_uploaded = match &solution {
+            Some(solution) => {
+                self.context
+                    .solution_cache()
+                    .was_uploaded(self.iid, solution)
+                    .await?
+            }
+            None => false,
+        };
+
+        // the solution is cached before uploading it, so a queued upload sent by the
+        // background retry can mark it as uploaded (see `UploadQueue::flush`)
+        let digest = solution.as_ref().map(|solution| solution.compute_digest());
+        if let Some(solution) = &solution {
+            self.context
+                .solution_cache()
+                .insert(
+                    self.iid,
+                    self.solver_run().config.solver_uuid,
+                    self.solver_run().run_uuid,
+                    solution,
+                    already_uploaded,
+                )
+                .await?;
+        }
 
-        self.upload_results(&result, meta.best_score, runtime)
+        if already_uploaded {
+            debug!("Identical solution for {:?} was already uploaded", self.iid);
+            self.upload_results(
+                &SolverResult::ValidCached,
+                None,
+                meta.best_score,
+                upload_runtime,
+                resource_usage,
+            )
             .await?;
+        } else {
+            let digest = digest.as_ref().map(|digest| digest.as_slice());
+            let outcome = self
+                .upload_results(
+                    &result,
+                    digest,
+                    meta.best_score,
+                    upload_runtime,
+                    resource_usage,
+                )
+                .await?;
+
+            // a queued upload may still be rejected or discarded, so only an accepted one counts
+            if let (UploadOutcome::Sent, Some(digest)) = (outcome, digest) {
+                self.context
+                    .solution_cache()
+                    .mark_uploaded(self.iid, digest)
+                    .await?;
+            }
+        }
+
         let result = self.to_result_type(&result, &meta);
 
         if !self.context.cmd_opts().keep_logs_on_success {
@@ -200,6 +351,9 @@ impl Job {
         Ok(JobResult {
             state: result,
             runtime,
+            resource_usage,
+            from_intermediate: executor.scored_from_intermediate(),
+            target_reached: executor.target_reached(),
         })
     }
 
@@ -211,46 +365,91 @@ impl Job {
         self.iid
     }
 
+    /// Index of the solver configuration within the run
+    pub fn solver(&self) -> usize {
+        self.solver
+    }
+
+    pub fn spec(&self) -> JobSpec {
+        JobSpec {
+            solver: self.solver,
+            iid: self.iid,
+            repetition: self.repetition,
+        }
+    }
+
+    /// Timeout of this job in seconds; `None` until the instance's metadata was fetched
+    pub fn timeout_sec(&self) -> Option<u64> {
+        self.timeout_sec.get().copied()
+    }
+
+    pub fn repetition(&self) -> u32 {
+        self.repetition
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.context.seed(self.repetition)
+    }
+
+    fn solver_run(&self) -> &SolverRun {
+        self.context.solver(self.solver)
+    }
+
     fn update_state(&self, state: JobState) {
         trace!("Runner {:?} switched into state: {:?}", self.iid, state);
         self.state.store(state, Ordering::Release);
+        self.context.events().emit(RunEvent::JobState {
+            job: self.context.job_ref(&self.spec()),
+            state,
+        });
     }
 
-    fn prepare_env_variables(&self, meta: &InstanceModel) -> Vec<(String, String)> {
+    fn prepare_env_variables(
+        &self,
+        meta: &InstanceModel,
+        timeout_sec: u64,
+    ) -> Vec<(String, String)> {
         if self.context.cmd_opts().no_env {
             return Vec::new();
         }
 
         let mut env = instance_to_env(meta);
-        env.push((
-            "STRIDE_TIMEOUT_SEC".into(),
-            self.context.cmd_opts().timeout.to_string(),
-        ));
+        env.push(("STRIDE_TIMEOUT_SEC".into(), timeout_sec.to_string()));
         env.push((
             "STRIDE_GRACE_SEC".into(),
             self.context.cmd_opts().grace.to_string(),
         ));
         env.push((
             "STRIDE_RUN_UUID".to_string(),
-            self.context.run_uuid().to_string(),
+            self.solver_run().run_uuid.to_string(),
         ));
-        if let Some(x) = self.context.cmd_opts().solver_uuid.as_ref() {
+        if let Some(x) = self.solver_run().config.solver_uuid.as_ref() {
             env.push(("STRIDE_SOLVER_UUID".to_string(), x.to_string()));
         }
+        env.push(("STRIDE_SEED".to_string(), self.seed().to_string()));
         env
     }
 
+    /// Failing to upload never fails the job; the result is queued for a later retry instead.
+    /// `digest` identifies the solution in the cache once a queued upload is accepted.
     async fn upload_results(
         &self,
         result: &SolverResult,
+        digest: Option<&[u8]>,
         best_score: Option<u32>,
         runtime: Duration,
-    ) -> anyhow::Result<()> {
+        resource_usage: Option<ResourceUsage>,
+    ) -> anyhow::Result<UploadOutcome> {
         if self.context.cmd_opts().no_upload {
-            return Ok(());
+            return Ok(UploadOutcome::NotUploaded);
+        }
+
+        // the user stopped the solver; the result says nothing about the solver's performance
+        if *result == SolverResult::Interrupted {
+            return Ok(UploadOutcome::NotUploaded);
         }
 
-        if self.context.cmd_opts().solver_uuid.is_none() {
+        if self.solver_run().config.solver_uuid.is_none() {
             let nice_result = match result {
                 SolverResult::Valid { data } => {
                     is_score_good_enough_for_upload(data.len() as u32, best_score)
@@ -260,22 +459,48 @@ impl Job {
             };
 
             if !nice_result {
-                return Ok(());
+                return Ok(UploadOutcome::NotUploaded);
             }
         }
 
-        let request = SolutionUploadRequestBuilder::default()
+        let mut builder = SolutionUploadRequestBuilder::default();
+        builder
             .instance_id(self.iid)
-            .run_uuid(self.context.run_uuid())
-            .solver_uuid(self.context.cmd_opts().solver_uuid)
+            .run_uuid(self.solver_run().run_uuid)
+            .solver_uuid(self.solver_run().config.solver_uuid)
             .seconds_computed(runtime.as_secs_f64())
-            .result(result)
-            .build()
-            .unwrap();
+            .result(result);
+
+        if let Some(usage) = resource_usage {
+            builder
+                .peak_memory_kb(usage.peak_rss_kb)
+                .user_seconds_computed(usage.user_time.as_secs_f64())
+                .system_seconds_computed(usage.system_time.as_secs_f64());
+        }
+
+        let request = builder.build().unwrap();
+        let json = request.to_json()?;
 
-        request.upload(self.context.server_conn()).await?;
+        let status = send_upload_request(self.context.server_conn(), self.iid, &json).await;
+        self.context.events().emit(RunEvent::Upload {
+            job: self.context.job_ref(&self.spec()),
+            status,
+        });
 
-        Ok(())
+        match status {
+            UploadStatus::Accepted => Ok(UploadOutcome::Sent),
+            UploadStatus::Rejected => {
+                warn!("Server rejected upload for {:?}", self.iid);
+                Ok(UploadOutcome::NotUploaded)
+            }
+            UploadStatus::Failed => {
+                self.context
+                    .upload_queue()
+                    .push(self.iid, &json, digest)
+                    .await?;
+                Ok(UploadOutcome::Queued)
+            }
+        }
     }
 
     fn to_result_type(&self, result: &SolverResult, meta: &InstanceModel) -> JobResultState {
@@ -297,11 +522,20 @@ impl Job {
                     }
                 }
             }
+            // only used for uploads; the executor never produces it
             SolverResult::ValidCached => unreachable!(),
             SolverResult::Infeasible => JobResultState::Infeasible,
-            SolverResult::SyntaxError => JobResultState::Error,
+            SolverResult::SyntaxError { message } => JobResultState::SyntaxError {
+                message: message.clone(),
+            },
+            SolverResult::Crashed { signal } => JobResultState::Crashed { signal: *signal },
+            SolverResult::NonZeroExitCode { code } => {
+                JobResultState::NonZeroExitCode { code: *code }
+            }
             SolverResult::Timeout => JobResultState::Timeout,
             SolverResult::IncompleteOutput => JobResultState::Incomplete,
+            SolverResult::MemoryLimitExceeded => JobResultState::MemoryLimitExceeded,
+            SolverResult::Interrupted => JobResultState::Interrupted,
         }
     }
 }
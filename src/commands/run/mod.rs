@@ -2,5 +2,9 @@ mod command;
 mod context;
 mod display;
 pub(crate) mod job;
+mod ordering;
+mod schedule;
+mod solver_config;
+mod timeout_policy;
 
 pub use command::command_run;
@@ -0,0 +1,112 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    commands::arguments::SizeMeasure,
+    utils::{run_summary_logger::RunSummaryEntry, IId},
+};
+
+/// Shuffles the instances; the same seed yields the same order
+pub fn shuffle(instances: &mut [IId], seed: u64) {
+    instances.sort_unstable();
+    instances.shuffle(&mut StdRng::seed_from_u64(seed));
+}
+
+/// Sorts by size as given by `sizes` (nodes, edges); ties are broken by IID
+pub fn sort_by_size(
+    instances: &mut [IId],
+    sizes: &HashMap<IId, (u32, u32)>,
+    measure: SizeMeasure,
+    largest_first: bool,
+) {
+    let size = |iid: &IId| {
+        sizes
+            .get(iid)
+            .map_or(0, |&(nodes, edges)| measure.of(nodes, edges))
+    };
+
+    if largest_first {
+        instances.sort_unstable_by_key(|iid| (Reverse(size(iid)), *iid));
+    } else {
+        instances.sort_unstable_by_key(|iid| (size(iid), *iid));
+    }
+}
+
+/// Sorts by the runtime in an earlier run, longest first; instances not contained in the
+/// earlier run come first, since they may be hard as well. Ties are broken by IID.
+pub fn sort_by_runtime(instances: &mut [IId], previous: &[RunSummaryEntry]) {
+    // the slowest repetition counts
+    let mut runtimes: HashMap<IId, f64> = HashMap::new();
+    for entry in previous {
+        let runtime = runtimes.entry(entry.iid).or_insert(entry.time_sec);
+        *runtime = runtime.max(entry.time_sec);
+    }
+
+    instances.sort_unstable_by(|a, b| {
+        let key = |iid: &IId| runtimes.get(iid).copied().unwrap_or(f64::INFINITY);
+        key(b).total_cmp(&key(a)).then(a.cmp(b))
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn iids(ids: &[u32]) -> Vec<IId> {
+        ids.iter().copied().map(IId::new).collect()
+    }
+
+    #[test]
+    fn shuffle_is_reproducible() {
+        let mut a: Vec<_> = (1..100).map(IId::new).collect();
+        let mut b = a.clone();
+        b.reverse();
+
+        shuffle(&mut a, 42);
+        shuffle(&mut b, 42);
+        assert_eq!(a, b);
+
+        let mut c = a.clone();
+        shuffle(&mut c, 43);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn sort_by_size() {
+        let sizes: HashMap<_, _> = [
+            (IId::new(1), (10, 5)),
+            (IId::new(2), (5, 20)),
+            (IId::new(3), (10, 1)),
+            (IId::new(4), (1, 1)),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut instances = iids(&[1, 2, 3, 4]);
+        super::sort_by_size(&mut instances, &sizes, SizeMeasure::Nodes, false);
+        assert_eq!(instances, iids(&[4, 2, 1, 3]));
+
+        super::sort_by_size(&mut instances, &sizes, SizeMeasure::Edges, true);
+        assert_eq!(instances, iids(&[2, 1, 3, 4]));
+
+        super::sort_by_size(&mut instances, &sizes, SizeMeasure::Total, true);
+        assert_eq!(instances, iids(&[2, 1, 3, 4]));
+    }
+
+    #[test]
+    fn sort_by_runtime() {
+        let entry = |iid, time_sec| RunSummaryEntry {
+            iid: IId::new(iid),
+            repetition: 0,
+            time_sec,
+            state: "best".into(),
+            score: Some(1),
+        };
+        let previous = vec![entry(1, 2.0), entry(2, 5.0), entry(3, 1.0), entry(3, 7.0)];
+
+        let mut instances = iids(&[1, 2, 3, 4, 5]);
+        super::sort_by_runtime(&mut instances, &previous);
+        assert_eq!(instances, iids(&[4, 5, 3, 2, 1]));
+    }
+}
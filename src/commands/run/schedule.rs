@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use crate::utils::IId;
+
+/// A single execution of a solver configuration on an instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JobSpec {
+    /// Index of the solver configuration within the run
+    pub solver: usize,
+    pub iid: IId,
+    /// Zero-based index among the `--repetitions` of the same instance and solver
+    pub repetition: u32,
+}
+
+/// Interleaves the repetitions and solvers per instance, so that all solvers process the
+/// instances in the same order and progress at a similar pace. Jobs whose (IID, repetition)
+/// is contained in `finished[solver]` are skipped.
+pub fn schedule_jobs(
+    instances: &[IId],
+    num_solvers: usize,
+    repetitions: u32,
+    finished: &[HashSet<(IId, u32)>],
+) -> Vec<JobSpec> {
+    instances
+        .iter()
+        .flat_map(|&iid| {
+            (0..repetitions).flat_map(move |repetition| {
+                (0..num_solvers).map(move |solver| JobSpec {
+                    solver,
+                    iid,
+                    repetition,
+                })
+            })
+        })
+        .filter(|job| {
+            !finished
+                .get(job.solver)
+                .is_some_and(|f| f.contains(&(job.iid, job.repetition)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spec(solver: usize, iid: u32, repetition: u32) -> JobSpec {
+        JobSpec {
+            solver,
+            iid: IId::new(iid),
+            repetition,
+        }
+    }
+
+    #[test]
+    fn interleave_solvers() {
+        let instances = [IId::new(3), IId::new(1), IId::new(2)];
+
+        assert_eq!(
+            schedule_jobs(&instances, 2, 1, &[]),
+            vec![
+                spec(0, 3, 0),
+                spec(1, 3, 0),
+                spec(0, 1, 0),
+                spec(1, 1, 0),
+                spec(0, 2, 0),
+                spec(1, 2, 0),
+            ]
+        );
+
+        let finished = [
+            HashSet::from([(IId::new(3), 0)]),
+            HashSet::from([(IId::new(1), 0)]),
+        ];
+        assert_eq!(
+            schedule_jobs(&instances, 2, 1, &finished),
+            vec![spec(1, 3, 0), spec(0, 1, 0), spec(0, 2, 0), spec(1, 2, 0)]
+        );
+    }
+
+    #[test]
+    fn repetitions() {
+        let instances = [IId::new(5), IId::new(4)];
+
+        assert_eq!(
+            schedule_jobs(&instances, 1, 3, &[]),
+            vec![
+                spec(0, 5, 0),
+                spec(0, 5, 1),
+                spec(0, 5, 2),
+                spec(0, 4, 0),
+                spec(0, 4, 1),
+                spec(0, 4, 2),
+            ]
+        );
+
+        let finished = [HashSet::from([(IId::new(5), 1), (IId::new(4), 0)])];
+        assert_eq!(
+            schedule_jobs(&instances, 1, 3, &finished),
+            vec![spec(0, 5, 0), spec(0, 5, 2), spec(0, 4, 1), spec(0, 4, 2)]
+        );
+    }
+}
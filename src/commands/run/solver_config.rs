@@ -0,0 +1,125 @@
+use std::{collections::HashSet, path::Path, path::PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A named solver configuration as given in the file passed to `run --solvers`
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SolverConfig {
+    pub name: String,
+    pub binary: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub solver_uuid: Option<Uuid>,
+}
+
+/// A solver configuration taking part in a run; each has its own Run UUID
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SolverRun {
+    #[serde(flatten)]
+    pub config: SolverConfig,
+    pub run_uuid: Uuid,
+}
+
+/// Reads a JSON list of solver configurations and checks that
+///  - there is at least one configuration
+///  - names are unique and can be used as directory names
+///  - all binaries exist
+pub fn read_solver_configs(path: &Path) -> anyhow::Result<Vec<SolverConfig>> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Opening solver configs {path:?}"))?;
+    let configs: Vec<SolverConfig> = serde_json::from_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("Parsing solver configs {path:?}"))?;
+
+    check_solver_configs(&configs)?;
+    Ok(configs)
+}
+
+fn check_solver_configs(configs: &[SolverConfig]) -> anyhow::Result<()> {
+    if configs.is_empty() {
+        anyhow::bail!("No solver configurations given");
+    }
+
+    let mut names = HashSet::new();
+    for config in configs {
+        let valid_name = !config.name.is_empty()
+            && config
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && !config.name.starts_with('.');
+
+        if !valid_name {
+            anyhow::bail!(
+                "Invalid solver name {:?}; only use letters, digits, '-', '_' and '.'",
+                config.name
+            );
+        }
+
+        if !names.insert(config.name.as_str()) {
+            anyhow::bail!("Solver name {:?} is used multiple times", config.name);
+        }
+
+        if !config.binary.is_file() {
+            anyhow::bail!(
+                "Solver binary {:?} of {:?} not found",
+                config.binary,
+                config.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(name: &str, binary: &str) -> SolverConfig {
+        SolverConfig {
+            name: name.into(),
+            binary: binary.into(),
+            args: Vec::new(),
+            solver_uuid: None,
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let configs: Vec<SolverConfig> = serde_json::from_str(
+            r#"[
+                {"name": "baseline", "binary": "./solver"},
+                {"name": "new", "binary": "./solver", "args": ["--fast"],
+                 "solver_uuid": "00000000-0000-0000-0000-000000000001"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(configs[0], config("baseline", "./solver"));
+        assert_eq!(configs[1].args, vec!["--fast".to_string()]);
+        assert_eq!(configs[1].solver_uuid, Some(Uuid::from_u128(1)));
+
+        assert!(serde_json::from_str::<Vec<SolverConfig>>(
+            r#"[{"name": "x", "binary": "./solver", "typo": 1}]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn check() {
+        let binary = std::env::current_exe().unwrap();
+        let binary = binary.to_str().unwrap();
+
+        assert!(check_solver_configs(&[config("a", binary), config("b-2.0_x", binary)]).is_ok());
+        assert!(check_solver_configs(&[]).is_err());
+        assert!(check_solver_configs(&[config("a", binary), config("a", binary)]).is_err());
+        assert!(check_solver_configs(&[config("a/b", binary)]).is_err());
+        assert!(check_solver_configs(&[config("..", binary)]).is_err());
+        assert!(check_solver_configs(&[config("", binary)]).is_err());
+        assert!(check_solver_configs(&[config("a", "/does/not/exist")]).is_err());
+    }
+}
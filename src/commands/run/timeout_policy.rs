@@ -0,0 +1,174 @@
+use std::{collections::BTreeMap, io::BufRead, path::Path};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{commands::arguments::RunOpts, utils::meta_data_db::InstanceModel};
+
+/// Determines the timeout of each instance. The timeout is `base + per_size * (nodes + edges)`
+/// (rounded up), unless the instance is listed in `per_instance`; the result is clamped
+/// to `[min, max]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeoutPolicy {
+    base: u64,
+    #[serde(default)]
+    per_size: Option<f64>,
+    /// Timeouts read from `--timeout-file`; keyed by IID
+    #[serde(default)]
+    per_instance: BTreeMap<u32, u64>,
+    #[serde(default)]
+    min: Option<u64>,
+    #[serde(default)]
+    max: Option<u64>,
+}
+
+impl TimeoutPolicy {
+    pub fn fixed(timeout: u64) -> Self {
+        Self {
+            base: timeout,
+            per_size: None,
+            per_instance: BTreeMap::new(),
+            min: None,
+            max: None,
+        }
+    }
+
+    pub fn try_from_opts(opts: &RunOpts) -> anyhow::Result<Self> {
+        if let (Some(min), Some(max)) = (opts.min_timeout, opts.max_timeout) {
+            if min > max {
+                anyhow::bail!("--min-timeout ({min}) must not exceed --max-timeout ({max})");
+            }
+        }
+
+        if let Some(per_size) = opts.timeout_per_size {
+            if !per_size.is_finite() || per_size < 0.0 {
+                anyhow::bail!("--timeout-per-size must be a non-negative number");
+            }
+        }
+
+        let per_instance = match &opts.timeout_file {
+            Some(path) => read_timeout_file(path)?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self {
+            base: opts.timeout,
+            per_size: opts.timeout_per_size,
+            per_instance,
+            min: opts.min_timeout,
+            max: opts.max_timeout,
+        })
+    }
+
+    /// Timeout in seconds for the instance
+    pub fn timeout_for(&self, instance: &InstanceModel) -> u64 {
+        let timeout = match self.per_instance.get(&instance.iid.iid_to_u32()) {
+            Some(&timeout) => timeout,
+            None => {
+                let size = instance.nodes as f64 + instance.edges as f64;
+                let scaled = self.per_size.map_or(0.0, |factor| (factor * size).ceil());
+                self.base.saturating_add(scaled as u64)
+            }
+        };
+
+        let timeout = self.min.map_or(timeout, |min| timeout.max(min));
+        self.max.map_or(timeout, |max| timeout.min(max))
+    }
+}
+
+/// Reads one `IID SECONDS` pair per line (separated by whitespace or a comma).
+/// Empty lines and lines starting with 'c' are ignored.
+fn read_timeout_file(path: &Path) -> anyhow::Result<BTreeMap<u32, u64>> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Opening timeout file {path:?}"))?;
+    let reader = std::io::BufReader::new(file);
+
+    let mut timeouts = BTreeMap::new();
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+
+        let parse = || -> Option<(u32, u64)> {
+            let mut parts = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|p| !p.is_empty());
+            let iid = parts.next()?.parse().ok()?;
+            let timeout = parts.next()?.parse().ok()?;
+            parts.next().is_none().then_some((iid, timeout))
+        };
+
+        let (iid, timeout) = parse().with_context(|| {
+            format!(
+                "Line {} of timeout file {path:?} is not of the form `IID SECONDS`: {line}",
+                line_no + 1
+            )
+        })?;
+        timeouts.insert(iid, timeout);
+    }
+
+    Ok(timeouts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::{DId, IId};
+    use tempdir::TempDir;
+
+    fn instance(iid: u32, nodes: u32, edges: u32) -> InstanceModel {
+        InstanceModel {
+            iid: IId::new(iid),
+            data_did: DId::new(iid),
+            nodes,
+            edges,
+            best_score: None,
+            diameter: None,
+            treewidth: None,
+            planar: None,
+            bipartite: None,
+        }
+    }
+
+    #[test]
+    fn timeout_for() {
+        let fixed = TimeoutPolicy::fixed(30);
+        assert_eq!(fixed.timeout_for(&instance(1, 1000, 5000)), 30);
+
+        let mut policy = TimeoutPolicy {
+            per_size: Some(0.01),
+            per_instance: [(2, 500)].into_iter().collect(),
+            ..TimeoutPolicy::fixed(10)
+        };
+        assert_eq!(policy.timeout_for(&instance(1, 100, 201)), 14);
+        assert_eq!(policy.timeout_for(&instance(2, 100, 201)), 500);
+
+        policy.min = Some(12);
+        policy.max = Some(100);
+        assert_eq!(policy.timeout_for(&instance(1, 0, 0)), 12);
+        assert_eq!(policy.timeout_for(&instance(1, 100_000, 0)), 100);
+        assert_eq!(policy.timeout_for(&instance(2, 0, 0)), 100);
+    }
+
+    #[test]
+    fn read_timeout_file() {
+        let tmp_dir = TempDir::new("timeout-test").unwrap();
+        let path = tmp_dir.path().join("timeouts.txt");
+
+        std::fs::write(&path, "c comment\n1 10\n\n 2,20 \n3\t30\n").unwrap();
+        let timeouts = super::read_timeout_file(&path).unwrap();
+        assert_eq!(
+            timeouts.into_iter().collect::<Vec<_>>(),
+            vec![(1, 10), (2, 20), (3, 30)]
+        );
+
+        std::fs::write(&path, "1 10 5\n").unwrap();
+        assert!(super::read_timeout_file(&path).is_err());
+
+        std::fs::write(&path, "1\n").unwrap();
+        assert!(super::read_timeout_file(&path).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use console::Style;
+
+use crate::utils::{
+    directory::StrideDirectory,
+    instance_data_db::InstanceDataDB,
+    instance_stats::{size_bucket_label, InstanceStats, PropertyCounts},
+    meta_data_db::MetaDataDB,
+};
+
+use super::{
+    arguments::{CommonOpts, StatsOpts},
+    common::select_instances_if_given,
+};
+
+pub async fn command_stats(_common_opts: &CommonOpts, cmd_opts: &StatsOpts) -> anyhow::Result<()> {
+    let stride_dir = StrideDirectory::try_default()?;
+    let meta_db = MetaDataDB::new(stride_dir.db_meta_file().as_path()).await?;
+    let instance_db = InstanceDataDB::new(stride_dir.db_instance_file().as_path()).await?;
+
+    // without a selection, all instances are summarized
+    let selected = select_instances_if_given(
+        &meta_db,
+        None,
+        cmd_opts.filter.as_deref(),
+        cmd_opts.sql_where.as_deref(),
+    )
+    .await?;
+
+    let mut instances = meta_db.fetch_all_instances().await?;
+    if let Some(selected) = selected {
+        let selected: HashSet<_> = selected.into_iter().collect();
+        instances.retain(|instance| selected.contains(&instance.iid));
+    }
+
+    let cached = instance_db.fetch_cached_dids().await?;
+    let stats = InstanceStats::new(&instances, &cached);
+
+    print_stats(&stats);
+    Ok(())
+}
+
+fn print_stats(stats: &InstanceStats) {
+    let bold = Style::new().bold();
+
+    println!(
+        "{} {}",
+        bold.apply_to("Instances:"),
+        Style::new().green().apply_to(stats.instances)
+    );
+    if stats.instances == 0 {
+        return;
+    }
+
+    let share = |count: usize| format!("{count:>8} ({:>5.1}%)", stats.percent(count));
+
+    println!("Cached locally:    {}", share(stats.cached));
+    println!();
+
+    println!(
+        "{}",
+        bold.apply_to(format!("{:<18} {:>17} {:>17}", "Size", "Nodes", "Edges"))
+    );
+    let buckets = stats
+        .node_buckets
+        .keys()
+        .chain(stats.edge_buckets.keys())
+        .copied()
+        .collect::<BTreeSet<_>>();
+    for bucket in buckets {
+        let count =
+            |buckets: &BTreeMap<u32, usize>| share(buckets.get(&bucket).copied().unwrap_or(0));
+        println!(
+            "{:<18} {} {}",
+            size_bucket_label(bucket),
+            count(&stats.node_buckets),
+            count(&stats.edge_buckets)
+        );
+    }
+    println!();
+
+    println!("{}", bold.apply_to("Known properties"));
+    println!("Best score:        {}", share(stats.best_score_known));
+    println!("Treewidth:         {}", share(stats.treewidth_known));
+    println!("Diameter:          {}", share(stats.diameter_known));
+    println!();
+
+    println!(
+        "{}",
+        bold.apply_to(format!(
+            "{:<18} {:>17} {:>17} {:>17}",
+            "", "yes", "no", "unknown"
+        ))
+    );
+    let property = |name: &str, counts: &PropertyCounts| {
+        println!(
+            "{name:<18} {} {} {}",
+            share(counts.yes),
+            share(counts.no),
+            share(counts.unknown)
+        );
+    };
+    property("Planar", &stats.planar);
+    property("Bipartite", &stats.bipartite);
+}
@@ -1,3 +1,10 @@
+use std::{
+    io::{BufRead, Error, ErrorKind, Result},
+    ops::Range,
+};
+
+use super::instance_reader::PaceReader;
+
 pub type Node = u32;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -27,3 +34,181 @@ impl Edge {
 
 pub type NumNodes = Node;
 pub type NumEdges = u64;
+
+/// Undirected graph in compressed sparse row format; each edge is stored in both directions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrGraph {
+    offsets: Vec<usize>,
+    neighbors: Vec<Node>,
+    number_of_edges: NumEdges,
+}
+
+impl CsrGraph {
+    /// Reads all edges and checks that they match the header of the instance
+    pub fn try_from_reader<R: BufRead>(reader: PaceReader<R>) -> Result<Self> {
+        let n = reader.number_of_nodes();
+        let m = reader.number_of_edges();
+
+        let mut edges = Vec::with_capacity(m as usize);
+        for edge in reader {
+            edges.push(edge?);
+        }
+
+        if edges.len() as NumEdges != m {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Header announces {m} edges, but {} edges were found",
+                    edges.len()
+                ),
+            ));
+        }
+
+        Self::try_from_edges(n, &edges)
+    }
+
+    pub fn try_from_edges(n: NumNodes, edges: &[Edge]) -> Result<Self> {
+        let mut offsets = vec![0usize; n as usize + 1];
+        for &Edge(u, v) in edges {
+            if u.max(v) >= n {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Edge contains node id larger than the number of nodes",
+                ));
+            }
+
+            offsets[u as usize + 1] += 1;
+            if u != v {
+                offsets[v as usize + 1] += 1;
+            }
+        }
+
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+
+        // `insert_at[u]` is the next free slot in the neighborhood of `u`
+        let mut insert_at = offsets.clone();
+        let mut neighbors = vec![0; offsets[n as usize]];
+        for &Edge(u, v) in edges {
+            neighbors[insert_at[u as usize]] = v;
+            insert_at[u as usize] += 1;
+
+            if u != v {
+                neighbors[insert_at[v as usize]] = u;
+                insert_at[v as usize] += 1;
+            }
+        }
+
+        Ok(Self {
+            offsets,
+            neighbors,
+            number_of_edges: edges.len() as NumEdges,
+        })
+    }
+
+    pub fn number_of_nodes(&self) -> NumNodes {
+        (self.offsets.len() - 1) as NumNodes
+    }
+
+    pub fn number_of_edges(&self) -> NumEdges {
+        self.number_of_edges
+    }
+
+    pub fn nodes(&self) -> Range<Node> {
+        0..self.number_of_nodes()
+    }
+
+    pub fn degree(&self, u: Node) -> NumNodes {
+        (self.offsets[u as usize + 1] - self.offsets[u as usize]) as NumNodes
+    }
+
+    pub fn neighbors(&self, u: Node) -> &[Node] {
+        &self.neighbors[self.offsets[u as usize]..self.offsets[u as usize + 1]]
+    }
+
+    /// Iterates over `u` followed by all its neighbors
+    pub fn closed_neighbors(&self, u: Node) -> impl Iterator<Item = Node> + '_ {
+        std::iter::once(u).chain(self.neighbors(u).iter().copied())
+    }
+
+    /// Number of connected components; isolated nodes form components of their own
+    pub fn number_of_connected_components(&self) -> NumNodes {
+        let mut visited = vec![false; self.number_of_nodes() as usize];
+        let mut stack = Vec::new();
+        let mut components = 0;
+
+        for start in self.nodes() {
+            if visited[start as usize] {
+                continue;
+            }
+
+            components += 1;
+            visited[start as usize] = true;
+            stack.push(start);
+
+            while let Some(u) = stack.pop() {
+                for &v in self.neighbors(u) {
+                    if !visited[v as usize] {
+                        visited[v as usize] = true;
+                        stack.push(v);
+                    }
+                }
+            }
+        }
+
+        components
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(data: &str) -> Result<CsrGraph> {
+        CsrGraph::try_from_reader(PaceReader::try_new(data.as_bytes())?)
+    }
+
+    #[test]
+    fn from_reader() {
+        let graph = read("p ds 5 4\n1 2\n2 3\n4 2\nc comment\n3 4\n").unwrap();
+
+        assert_eq!(graph.number_of_nodes(), 5);
+        assert_eq!(graph.number_of_edges(), 4);
+        assert_eq!(graph.nodes().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+        let degrees: Vec<_> = graph.nodes().map(|u| graph.degree(u)).collect();
+        assert_eq!(degrees, vec![1, 3, 2, 2, 0]);
+
+        let mut neighbors = graph.neighbors(1).to_vec();
+        neighbors.sort();
+        assert_eq!(neighbors, vec![0, 2, 3]);
+
+        assert_eq!(graph.closed_neighbors(0).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(graph.closed_neighbors(4).collect::<Vec<_>>(), vec![4]);
+    }
+
+    #[test]
+    fn header_mismatch() {
+        assert!(read("p ds 3 3\n1 2\n2 3\n").is_err());
+        assert!(read("p ds 3 1\n1 2\n2 3\n").is_err());
+        assert!(read("p ds 3 2\n1 2\n2 4\n").is_err());
+        assert!(read("p ds 3 2\n1 2\n0 3\n").is_err());
+    }
+
+    #[test]
+    fn self_loop() {
+        let graph = CsrGraph::try_from_edges(2, &[Edge(0, 0), Edge(0, 1)]).unwrap();
+        assert_eq!(graph.degree(0), 2);
+        assert_eq!(graph.degree(1), 1);
+    }
+
+    #[test]
+    fn connected_components() {
+        let graph = read("p ds 7 4\n1 2\n2 3\n4 5\n6 6\n").unwrap();
+        assert_eq!(graph.number_of_connected_components(), 4);
+
+        let graph = CsrGraph::try_from_edges(0, &[]).unwrap();
+        assert_eq!(graph.number_of_connected_components(), 0);
+    }
+}
@@ -137,8 +137,12 @@ impl<R: BufRead> PaceReader<R> {
             let from = parse_next_value!(parts, "Source node");
             let dest = parse_next_value!(parts, "Target node");
 
-            debug_assert!((1..=self.number_of_nodes).contains(&from));
-            debug_assert!((1..=self.number_of_nodes).contains(&dest));
+            raise_error_unless!(
+                (1..=self.number_of_nodes).contains(&from)
+                    && (1..=self.number_of_nodes).contains(&dest),
+                ErrorKind::InvalidData,
+                "Node id out of range"
+            );
 
             Ok(Some(Edge(from, dest)))
         } else {
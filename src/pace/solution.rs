@@ -1,13 +1,90 @@
 use sha1::{digest::Output, Digest, Sha1};
 
 use super::graph::*;
-use std::{
-    collections::HashSet,
-    io::{BufRead, Write},
-};
+use std::io::{BufRead, Write};
 
 pub type Result<T> = std::io::Result<T>;
 
+/// Number of undominated (or out-of-range) nodes listed in a [`VerificationReport`]
+const MAX_NODE_SAMPLE: usize = 10;
+
+/// Result of checking a solution against a graph; all node ids are 1-indexed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub number_of_nodes: NumNodes,
+    pub solution_size: NumNodes,
+    pub num_undominated: NumNodes,
+    /// The smallest undominated nodes (at most 10)
+    pub undominated_sample: Vec<Node>,
+    /// Number of nodes of the solution that do not exist in the graph
+    pub num_out_of_range: NumNodes,
+    /// The first out-of-range nodes of the solution (at most 10)
+    pub out_of_range_sample: Vec<Node>,
+}
+
+fn join_nodes(nodes: &[Node], separator: &str) -> String {
+    nodes
+        .iter()
+        .map(|u| u.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+impl VerificationReport {
+    pub fn is_feasible(&self) -> bool {
+        self.num_undominated == 0 && self.num_out_of_range == 0
+    }
+
+    /// One-line description of the problems found, e.g., for error messages
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.num_undominated > 0 {
+            parts.push(format!(
+                "{} of {} nodes undominated (e.g., {})",
+                self.num_undominated,
+                self.number_of_nodes,
+                join_nodes(&self.undominated_sample, ", ")
+            ));
+        }
+        if self.num_out_of_range > 0 {
+            parts.push(format!(
+                "{} out-of-range nodes (e.g., {})",
+                self.num_out_of_range,
+                join_nodes(&self.out_of_range_sample, ", ")
+            ));
+        }
+
+        if parts.is_empty() {
+            "feasible".into()
+        } else {
+            parts.join("; ")
+        }
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(
+            writer,
+            "feasible: {}",
+            if self.is_feasible() { "yes" } else { "no" }
+        )?;
+        writeln!(writer, "nodes in graph: {}", self.number_of_nodes)?;
+        writeln!(writer, "nodes in solution: {}", self.solution_size)?;
+        writeln!(writer, "undominated nodes: {}", self.num_undominated)?;
+        writeln!(
+            writer,
+            "undominated sample: {}",
+            join_nodes(&self.undominated_sample, " ")
+        )?;
+        writeln!(writer, "out-of-range nodes: {}", self.num_out_of_range)?;
+        writeln!(
+            writer,
+            "out-of-range sample: {}",
+            join_nodes(&self.out_of_range_sample, " ")
+        )?;
+        Ok(())
+    }
+}
+
 pub struct Solution {
     pub solution: Vec<Node>,
 }
@@ -98,39 +175,67 @@ impl Solution {
     }
 
     /// Verifies that the solution is a valid dominating set for the given graph.
+    pub fn valid_domset_for_graph(&self, graph: &CsrGraph) -> bool {
+        self.verify(graph).is_feasible()
+    }
+
+    /// Verifies that the solution is a valid dominating set for the given graph.
+    /// Fails if an edge or a node of the solution exceeds the number of nodes.
+    #[deprecated(note = "build a `CsrGraph` once and use `valid_domset_for_graph` or `verify`")]
     pub fn valid_domset_for_instance(
         &self,
         n: NumNodes,
         edges: impl Iterator<Item = Edge>,
     ) -> Result<bool> {
-        // TODO: we are building a complete adj list; we should refactor that out!
-        let mut adjlist = (0..n).map(|_| Vec::new()).collect::<Vec<_>>();
-        for Edge(u, v) in edges {
-            if u.max(v) >= n {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Edge contains node id larger than the number of nodes",
-                ));
-            }
+        let graph = CsrGraph::try_from_edges(n, &edges.collect::<Vec<_>>())?;
+        let report = self.verify(&graph);
 
-            adjlist[u as usize].push(v);
-            adjlist[v as usize].push(u);
+        if report.num_out_of_range > 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Node id in solution larger than the number of nodes",
+            ));
         }
 
-        let mut covered = HashSet::<Node>::with_capacity(n as usize);
+        Ok(report.is_feasible())
+    }
+
+    /// Checks which nodes of the graph are not dominated by the solution
+    pub fn verify(&self, graph: &CsrGraph) -> VerificationReport {
+        let n = graph.number_of_nodes();
+        let mut covered = vec![false; n as usize];
+        let mut num_out_of_range = 0;
+        let mut out_of_range_sample = Vec::new();
+
         for &u in &self.solution {
             if u >= n {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Node id in solution larger than the number of nodes",
-                ));
+                num_out_of_range += 1;
+                if out_of_range_sample.len() < MAX_NODE_SAMPLE {
+                    out_of_range_sample.push(u + 1);
+                }
+                continue;
             }
 
-            covered.insert(u);
-            covered.extend(adjlist[u as usize].iter().copied());
+            for v in graph.closed_neighbors(u) {
+                covered[v as usize] = true;
+            }
         }
 
-        Ok(covered.len() == n as usize)
+        let mut undominated = graph.nodes().filter(|&u| !covered[u as usize]);
+        let undominated_sample: Vec<_> = undominated
+            .by_ref()
+            .take(MAX_NODE_SAMPLE)
+            .map(|u| u + 1)
+            .collect();
+
+        VerificationReport {
+            number_of_nodes: n,
+            solution_size: self.solution.len() as NumNodes,
+            num_undominated: undominated_sample.len() as NumNodes + undominated.count() as NumNodes,
+            undominated_sample,
+            num_out_of_range,
+            out_of_range_sample,
+        }
     }
 
     pub fn compute_digest(&self) -> Output<Sha1> {
@@ -298,6 +403,22 @@ mod test {
 
     #[test]
     fn test_domset_verifier() {
+        let graph = CsrGraph::try_from_edges(4, &[Edge(0, 1), Edge(2, 3)]).unwrap();
+
+        assert!(!Solution { solution: vec![0] }.valid_domset_for_graph(&graph));
+        assert!(Solution {
+            solution: vec![0, 2]
+        }
+        .valid_domset_for_graph(&graph));
+        assert!(!Solution {
+            solution: vec![0, 2, 4]
+        }
+        .valid_domset_for_graph(&graph));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_domset_verifier_for_instance() {
         let edges = [Edge(0, 1), Edge(2, 3)];
 
         assert!(!Solution { solution: vec![0] }
@@ -309,5 +430,68 @@ mod test {
         }
         .valid_domset_for_instance(4, edges.iter().copied())
         .unwrap());
+
+        assert!(Solution {
+            solution: vec![0, 4]
+        }
+        .valid_domset_for_instance(4, edges.iter().copied())
+        .is_err());
+        assert!(Solution { solution: vec![0] }
+            .valid_domset_for_instance(2, edges.iter().copied())
+            .is_err());
+    }
+
+    #[test]
+    fn verification_report() {
+        // path 0-1-2 and isolated nodes 3..15
+        let graph = CsrGraph::try_from_edges(15, &[Edge(0, 1), Edge(1, 2)]).unwrap();
+        let report = Solution {
+            solution: vec![1, 20],
+        }
+        .verify(&graph);
+
+        assert_eq!(
+            report,
+            VerificationReport {
+                number_of_nodes: 15,
+                solution_size: 2,
+                num_undominated: 12,
+                undominated_sample: (4..14).collect(),
+                num_out_of_range: 1,
+                out_of_range_sample: vec![21],
+            }
+        );
+        assert!(!report.is_feasible());
+        assert_eq!(
+            report.summary(),
+            "12 of 15 nodes undominated (e.g., 4, 5, 6, 7, 8, 9, 10, 11, 12, 13); 1 out-of-range nodes (e.g., 21)"
+        );
+
+        let mut buffer = Vec::new();
+        report.write(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "feasible: no\n\
+            nodes in graph: 15\n\
+            nodes in solution: 2\n\
+            undominated nodes: 12\n\
+            undominated sample: 4 5 6 7 8 9 10 11 12 13\n\
+            out-of-range nodes: 1\n\
+            out-of-range sample: 21\n"
+        );
+
+        // out-of-range nodes are capped like undominated ones
+        let report = Solution {
+            solution: (0..1000).collect(),
+        }
+        .verify(&graph);
+        assert_eq!(report.num_out_of_range, 985);
+        assert_eq!(report.out_of_range_sample, (16..26).collect::<Vec<_>>());
+        assert_eq!(report.num_undominated, 0);
+
+        let report = Solution { solution: vec![1] }
+            .verify(&CsrGraph::try_from_edges(3, &[Edge(0, 1), Edge(1, 2)]).unwrap());
+        assert!(report.is_feasible());
+        assert_eq!(report.summary(), "feasible");
     }
 }
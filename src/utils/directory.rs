@@ -4,6 +4,7 @@ const PATH_CONFIG: &str = "config.json";
 const PATH_DB_META: &str = "metadata.db";
 const PATH_DB_CACHE: &str = "cache.db";
 const PATH_DB_INSTANCES: &str = "instances.db";
+const PATH_DB_UPLOAD_QUEUE: &str = "uploads.db";
 
 const DATA_DIR: &str = ".stride";
 
@@ -50,6 +51,10 @@ impl StrideDirectory {
     pub fn db_instance_file(&self) -> PathBuf {
         self.data_dir.join(PATH_DB_INSTANCES)
     }
+
+    pub fn db_upload_queue_file(&self) -> PathBuf {
+        self.data_dir.join(PATH_DB_UPLOAD_QUEUE)
+    }
 }
 
 #[cfg(test)]
@@ -107,4 +112,5 @@ mod test {
     check_filename!(db_meta_file, PATH_DB_META);
     check_filename!(db_cache_file, PATH_DB_CACHE);
     check_filename!(db_instance_file, PATH_DB_INSTANCES);
+    check_filename!(db_upload_queue_file, PATH_DB_UPLOAD_QUEUE);
 }
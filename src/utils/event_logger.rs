@@ -0,0 +1,267 @@
+use std::{
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use serde::Serialize;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::commands::run::job::{JobResult, JobState};
+
+use super::{solution_upload::UploadStatus, IId};
+
+/// Identifies a job within the events of a run
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRef {
+    pub iid: u32,
+    pub repetition: u32,
+    /// Name of the solver configuration; only set in multi-solver mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solver: Option<String>,
+}
+
+impl JobRef {
+    pub fn new(iid: IId, repetition: u32, solver: Option<&str>) -> Self {
+        Self {
+            iid: iid.iid_to_u32(),
+            repetition,
+            solver: solver.map(String::from),
+        }
+    }
+}
+
+/// Serializable form of a [`JobResult`]
+#[derive(Debug, Clone, Serialize)]
+pub struct JobResultRecord {
+    pub state: String,
+    pub score: Option<u32>,
+    pub best_known: Option<u32>,
+    pub details: Option<String>,
+    pub time_sec: f64,
+    pub peak_rss_kb: Option<u64>,
+    pub user_time_sec: Option<f64>,
+    pub system_time_sec: Option<f64>,
+    pub from_intermediate: bool,
+    /// Seconds until an intermediate solution reached the best known score (`--stop-at-best`)
+    pub target_reached_sec: Option<f64>,
+}
+
+impl From<&JobResult> for JobResultRecord {
+    fn from(result: &JobResult) -> Self {
+        use crate::commands::run::job::JobResultState::*;
+
+        let best_known = match result.state {
+            BestKnown { score } => Some(score),
+            Suboptimal { best_known, .. } => Some(best_known),
+            _ => None,
+        };
+        let usage = result.resource_usage.as_ref();
+
+        Self {
+            state: result.state.to_string(),
+            score: result.state.score(),
+            best_known,
+            details: result.state.details(),
+            time_sec: result.runtime.as_secs_f64(),
+            peak_rss_kb: usage.map(|u| u.peak_rss_kb),
+            user_time_sec: usage.map(|u| u.user_time.as_secs_f64()),
+            system_time_sec: usage.map(|u| u.system_time.as_secs_f64()),
+            from_intermediate: result.from_intermediate,
+            target_reached_sec: result.target_reached.map(|t| t.as_secs_f64()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RunEvent {
+    RunStarted {
+        run_uuid: Uuid,
+        jobs: usize,
+        solvers: Vec<String>,
+    },
+    JobStarted {
+        #[serde(flatten)]
+        job: JobRef,
+    },
+    JobState {
+        #[serde(flatten)]
+        job: JobRef,
+        state: JobState,
+    },
+    JobFinished {
+        #[serde(flatten)]
+        job: JobRef,
+        result: JobResultRecord,
+    },
+    Upload {
+        #[serde(flatten)]
+        job: JobRef,
+        /// `failed` uploads are queued for a later retry
+        status: UploadStatus,
+    },
+    RunFinished {
+        run_uuid: Uuid,
+        finished: usize,
+        not_started: usize,
+        interrupted: bool,
+        pending_uploads: usize,
+    },
+}
+
+#[derive(Serialize)]
+struct TimedEvent<'a> {
+    time: String,
+    #[serde(flatten)]
+    event: &'a RunEvent,
+}
+
+/// Writes one JSON object per line for each event of a run (`run --json-events`)
+#[derive(Clone, Default)]
+pub struct EventLogger {
+    writer: Option<Arc<Mutex<Box<dyn Write + Send>>>>,
+}
+
+impl EventLogger {
+    /// A logger that drops all events
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Writes to stdout if `path` is `-`; otherwise to the file, which is appended to if `append` is set
+    pub fn try_new(path: &Path, append: bool) -> anyhow::Result<Self> {
+        let writer: Box<dyn Write + Send> = if path == Path::new("-") {
+            Box::new(std::io::stdout())
+        } else {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(append)
+                .truncate(!append)
+                .open(path)
+                .with_context(|| format!("Opening event log {path:?}"))?;
+            Box::new(file)
+        };
+
+        Ok(Self::from_writer(writer))
+    }
+
+    pub fn from_writer(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Some(Arc::new(Mutex::new(writer))),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Failing to write an event never fails the run
+    pub fn emit(&self, event: RunEvent) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+
+        let line = match serde_json::to_string(&TimedEvent {
+            time: chrono::Local::now().to_rfc3339(),
+            event: &event,
+        }) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize event {event:?}: {e}");
+                return;
+            }
+        };
+
+        let mut writer = writer.lock().unwrap();
+        // each event is flushed, so that consumers see it immediately
+        if let Err(e) = writeln!(writer, "{line}").and_then(|_| writer.flush()) {
+            warn!("Failed to write event: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::commands::run::job::JobResultState;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_lines() {
+        let buffer = SharedBuffer::default();
+        let logger = EventLogger::from_writer(Box::new(buffer.clone()));
+
+        let job = JobRef::new(IId::new(12), 1, None);
+        logger.emit(RunEvent::JobState {
+            job: job.clone(),
+            state: JobState::Running,
+        });
+        logger.emit(RunEvent::JobFinished {
+            job,
+            result: (&JobResult {
+                state: JobResultState::Suboptimal {
+                    score: 5,
+                    best_known: 4,
+                },
+                runtime: std::time::Duration::from_millis(1500),
+                resource_usage: None,
+                from_intermediate: false,
+                target_reached: None,
+            })
+                .into(),
+        });
+        logger.emit(RunEvent::Upload {
+            job: JobRef::new(IId::new(3), 0, Some("fast")),
+            status: UploadStatus::Failed,
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<serde_json::Value> = output
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(events.len(), 3);
+
+        assert_eq!(events[0]["event"], "job_state");
+        assert_eq!(events[0]["iid"], 12);
+        assert_eq!(events[0]["repetition"], 1);
+        assert_eq!(events[0]["state"], "running");
+        assert!(events[0]["time"].is_string());
+        assert!(events[0].get("solver").is_none());
+
+        assert_eq!(events[1]["event"], "job_finished");
+        assert_eq!(events[1]["result"]["state"], "suboptimal");
+        assert_eq!(events[1]["result"]["score"], 5);
+        assert_eq!(events[1]["result"]["best_known"], 4);
+        assert_eq!(events[1]["result"]["time_sec"], 1.5);
+
+        assert_eq!(events[2]["event"], "upload");
+        assert_eq!(events[2]["solver"], "fast");
+        assert_eq!(events[2]["status"], "failed");
+    }
+
+    #[test]
+    fn disabled() {
+        let logger = EventLogger::disabled();
+        assert!(!logger.is_enabled());
+        logger.emit(RunEvent::JobStarted {
+            job: JobRef::new(IId::new(1), 0, None),
+        });
+    }
+}
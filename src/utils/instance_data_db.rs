@@ -1,6 +1,6 @@
 use anyhow::Context;
 use meta_data_db::MetaDataDB;
-use std::path::Path;
+use std::{collections::HashSet, path::Path};
 use tokio::sync::Mutex;
 use tracing::{debug, trace};
 
@@ -93,6 +93,31 @@ impl InstanceDataDB {
         }
     }
 
+    /// Returns whether the data of the instance is stored locally
+    pub async fn contains(&self, did: DId) -> anyhow::Result<bool> {
+        let conn = self.instance_data_db.lock().await;
+
+        let found = conn
+            .prepare("SELECT 1 FROM InstanceData WHERE did = ?1 LIMIT 1")?
+            .exists([did.did_to_u32()])
+            .with_context(|| format!("Looking up {did:?} in DB"))?;
+
+        Ok(found)
+    }
+
+    /// Returns the data ids of all instances stored locally
+    pub async fn fetch_cached_dids(&self) -> anyhow::Result<HashSet<DId>> {
+        let conn = self.instance_data_db.lock().await;
+
+        let dids = conn
+            .prepare("SELECT did FROM InstanceData")?
+            .query_map([], |row| Ok(DId::new(row.get(0)?)))?
+            .collect::<Result<_, _>>()
+            .context("Fetching cached data ids")?;
+
+        Ok(dids)
+    }
+
     async fn insert_into_db(&self, did: DId, data: &str) -> anyhow::Result<()> {
         let conn = self.instance_data_db.lock().await;
 
@@ -109,14 +134,16 @@ impl InstanceDataDB {
         server_conn: &ServerConnection,
         iid: IId,
     ) -> anyhow::Result<String> {
+        server_conn.ensure_online(&format!(
+            "download instance {} since it is not cached locally",
+            iid.iid_to_u32()
+        ))?;
+
         let url = server_conn
             .base_url()
             .join(&format!("api/instances/download/{}", iid.0))?;
 
-        let resp = server_conn.client_arc().get(url).send().await?;
-        resp.error_for_status_ref()?;
-
-        Ok(resp.text().await?)
+        server_conn.get_text(url).await
     }
 
     pub async fn add_from_db_file(&self, other: &Path) -> anyhow::Result<()> {
@@ -0,0 +1,442 @@
+//! A small filter language to select instances by their metadata, e.g.,
+//! `nodes < 1000 and planar and treewidth <= 5 and best_score?`.
+//!
+//! ```text
+//! expr       := and_expr ("or" and_expr)*
+//! and_expr   := unary ("and" unary)*
+//! unary      := "not" unary | "(" expr ")" | condition
+//! condition  := FIELD "?"                  -- value is known
+//!             | FIELD                      -- boolean field is true
+//!             | FIELD OP VALUE             -- OP is one of < <= > >= = == !=
+//! ```
+//!
+//! Keywords and boolean values (`true`, `false`) are case-insensitive. A filter is translated
+//! into a parameterized SQL clause; only the whitelisted field names end up in the SQL text.
+
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldType {
+    Int,
+    Bool,
+}
+
+/// Fields of `InstanceModel` that may be used in a filter
+const FIELDS: [(&str, FieldType); 8] = [
+    ("iid", FieldType::Int),
+    ("nodes", FieldType::Int),
+    ("edges", FieldType::Int),
+    ("best_score", FieldType::Int),
+    ("diameter", FieldType::Int),
+    ("treewidth", FieldType::Int),
+    ("planar", FieldType::Bool),
+    ("bipartite", FieldType::Bool),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterError {
+    /// Zero-based character offset into the filter
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at column {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Question,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "`{s}`"),
+            Token::Int(x) => write!(f, "`{x}`"),
+            Token::Op(op) => write!(f, "`{op}`"),
+            Token::LParen => f.write_str("`(`"),
+            Token::RParen => f.write_str("`)`"),
+            Token::Question => f.write_str("`?`"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    const OPS: [&str; 7] = ["<=", ">=", "==", "!=", "<", ">", "="];
+
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        let start = pos;
+
+        if c.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let token = if c.is_ascii_alphabetic() || c == '_' {
+            while pos < chars.len() && (chars[pos].is_ascii_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            Token::Ident(chars[start..pos].iter().collect())
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(pos + 1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            pos += 1;
+            while pos < chars.len() && chars[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            let text: String = chars[start..pos].iter().collect();
+            Token::Int(text.parse().map_err(|_| FilterError {
+                position: start,
+                message: format!("Number {text} is out of range"),
+            })?)
+        } else if let Some(op) = OPS.into_iter().find(|op| {
+            op.chars()
+                .enumerate()
+                .all(|(i, c)| chars.get(pos + i) == Some(&c))
+        }) {
+            pos += op.len();
+            Token::Op(op)
+        } else {
+            pos += 1;
+            match c {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                '?' => Token::Question,
+                _ => {
+                    return Err(FilterError {
+                        position: start,
+                        message: format!("Unexpected character `{c}`"),
+                    })
+                }
+            }
+        };
+
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Known(&'static str),
+    Compare(&'static str, &'static str, i64),
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    /// Position reported for errors at the end of the input
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.end, |(p, _)| *p)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, FilterError> {
+        Self::error_at(self.position(), message)
+    }
+
+    fn error_at<T>(position: usize, message: String) -> Result<T, FilterError> {
+        Err(FilterError { position, message })
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse(mut self) -> Result<Expr, FilterError> {
+        if self.tokens.is_empty() {
+            return self.error("Filter is empty".into());
+        }
+
+        let expr = self.parse_or()?;
+        match self.peek() {
+            None => Ok(expr),
+            Some(token) => self.error(format!(
+                "Unexpected {token}; expected `and`, `or`, or the end"
+            )),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            self.next += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.parse_unary()?;
+        while self.next_is_keyword("and") {
+            self.next += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, FilterError> {
+        if self.next_is_keyword("not") {
+            self.next += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        match self.peek() {
+            Some(Token::LParen) => {
+                self.next += 1;
+                let expr = self.parse_or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return self.error("Expected `)`".into());
+                }
+                self.next += 1;
+                Ok(expr)
+            }
+            Some(Token::Ident(_)) => self.parse_condition(),
+            Some(token) => self.error(format!("Unexpected {token}; expected a field name")),
+            None => self.error("Unexpected end of filter; expected a field name".into()),
+        }
+    }
+
+    fn parse_condition(&mut self) -> Result<Expr, FilterError> {
+        let Some(Token::Ident(name)) = self.peek() else {
+            unreachable!()
+        };
+        let field_position = self.position();
+        let (field, field_type) = match lookup_field(name) {
+            Some(field) => field,
+            None => return self.error(unknown_field_message(name)),
+        };
+        self.next += 1;
+
+        match self.peek() {
+            Some(Token::Question) => {
+                self.next += 1;
+                Ok(Expr::Known(field))
+            }
+            Some(Token::Op(op)) => {
+                let op = *op;
+                if field_type == FieldType::Bool && !matches!(op, "=" | "==" | "!=") {
+                    return self.error(format!(
+                        "`{field}` is a boolean field and can only be compared with `=` or `!=`"
+                    ));
+                }
+                self.next += 1;
+
+                let value = self.parse_value(field, field_type)?;
+                Ok(Expr::Compare(field, op, value))
+            }
+            _ if field_type == FieldType::Bool => Ok(Expr::Compare(field, "=", 1)),
+            _ => Self::error_at(field_position, format!(
+                "`{field}` is a numeric field and must be compared to a value (e.g., `{field} < 100`) or checked for presence (`{field}?`)"
+            )),
+        }
+    }
+
+    fn parse_value(&mut self, field: &str, field_type: FieldType) -> Result<i64, FilterError> {
+        let value = match (self.peek(), field_type) {
+            (Some(Token::Int(x)), FieldType::Int) => *x,
+            (Some(Token::Int(x @ (0 | 1))), FieldType::Bool) => *x,
+            (Some(Token::Ident(s)), FieldType::Bool) if s.eq_ignore_ascii_case("true") => 1,
+            (Some(Token::Ident(s)), FieldType::Bool) if s.eq_ignore_ascii_case("false") => 0,
+            (Some(token), FieldType::Int) => {
+                return self.error(format!("Unexpected {token}; `{field}` expects a number"))
+            }
+            (Some(token), FieldType::Bool) => {
+                return self.error(format!(
+                    "Unexpected {token}; `{field}` expects `true` or `false`"
+                ))
+            }
+            (None, _) => return self.error(format!("Missing value for `{field}`")),
+        };
+
+        self.next += 1;
+        Ok(value)
+    }
+}
+
+fn lookup_field(name: &str) -> Option<(&'static str, FieldType)> {
+    FIELDS
+        .into_iter()
+        .find(|(field, _)| field.eq_ignore_ascii_case(name))
+}
+
+fn unknown_field_message(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    let suggestion = FIELDS
+        .iter()
+        .map(|(field, _)| (edit_distance(&lower, field), *field))
+        .min()
+        .filter(|(distance, _)| *distance <= 2.max(lower.len() / 3));
+
+    let known: Vec<_> = FIELDS.iter().map(|(field, _)| *field).collect();
+    match suggestion {
+        Some((_, field)) => format!("Unknown field `{name}`; did you mean `{field}`?"),
+        None => format!("Unknown field `{name}`; known fields: {}", known.join(", ")),
+    }
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// A validated filter on the `Instance` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceFilter {
+    expr: Expr,
+}
+
+impl InstanceFilter {
+    pub fn parse(input: &str) -> Result<Self, FilterError> {
+        let parser = Parser {
+            tokens: tokenize(input)?,
+            next: 0,
+            end: input.chars().count(),
+        };
+
+        Ok(Self {
+            expr: parser.parse()?,
+        })
+    }
+
+    /// Returns the WHERE clause with `?` placeholders and the values to bind to them
+    pub fn to_sql(&self) -> (String, Vec<i64>) {
+        let mut params = Vec::new();
+        let sql = Self::expr_to_sql(&self.expr, &mut params);
+        (sql, params)
+    }
+
+    fn expr_to_sql(expr: &Expr, params: &mut Vec<i64>) -> String {
+        match expr {
+            Expr::And(a, b) => format!(
+                "({} AND {})",
+                Self::expr_to_sql(a, params),
+                Self::expr_to_sql(b, params)
+            ),
+            Expr::Or(a, b) => format!(
+                "({} OR {})",
+                Self::expr_to_sql(a, params),
+                Self::expr_to_sql(b, params)
+            ),
+            // following SQL, an unknown value (NULL) satisfies neither a condition nor its negation
+            Expr::Not(a) => format!("(NOT {})", Self::expr_to_sql(a, params)),
+            Expr::Known(field) => format!("{field} IS NOT NULL"),
+            Expr::Compare(field, op, value) => {
+                params.push(*value);
+                let op = if *op == "==" { "=" } else { op };
+                format!("{field} {op} ?")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sql(filter: &str) -> (String, Vec<i64>) {
+        InstanceFilter::parse(filter).unwrap().to_sql()
+    }
+
+    fn error(filter: &str) -> FilterError {
+        InstanceFilter::parse(filter).unwrap_err()
+    }
+
+    #[test]
+    fn translate() {
+        assert_eq!(sql("nodes<1000"), ("nodes < ?".into(), vec![1000]));
+        assert_eq!(
+            sql("nodes<1000 and planar and treewidth<=5 and best_score?"),
+            (
+                "(((nodes < ? AND planar = ?) AND treewidth <= ?) AND best_score IS NOT NULL)"
+                    .into(),
+                vec![1000, 1, 5]
+            )
+        );
+        assert_eq!(
+            sql("Bipartite = True AND (nodes == 1337 or EDGES != -1)"),
+            (
+                "(bipartite = ? AND (nodes = ? OR edges != ?))".into(),
+                vec![1, 1337, -1]
+            )
+        );
+        assert_eq!(
+            sql("not planar or not (diameter > 3)"),
+            (
+                "((NOT planar = ?) OR (NOT diameter > ?))".into(),
+                vec![1, 3]
+            )
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let e = error("node < 10");
+        assert_eq!(e.position, 0);
+        assert!(e.message.contains("did you mean `nodes`"), "{e}");
+
+        assert!(error("foobar < 10").message.contains("known fields"));
+
+        let e = error("nodes < 10 and treewidth");
+        assert_eq!(e.position, 15);
+        assert!(e.message.contains("numeric field"), "{e}");
+
+        assert!(error("planar < 1").message.contains("boolean field"));
+        assert!(error("nodes < true").message.contains("expects a number"));
+        assert!(error("planar = 5").message.contains("expects `true`"));
+        assert!(error("nodes <").message.contains("Missing value"));
+        assert!(error("(nodes < 5").message.contains("Expected `)`"));
+        assert!(error("nodes < 5 nodes").message.contains("expected `and`"));
+        assert!(error("nodes; drop")
+            .message
+            .contains("Unexpected character"));
+        assert!(error("  ").message.contains("empty"));
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(super::edit_distance("nodes", "nodes"), 0);
+        assert_eq!(super::edit_distance("node", "nodes"), 1);
+        assert_eq!(super::edit_distance("treewdith", "treewidth"), 2);
+        assert_eq!(super::edit_distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::{meta_data_db::InstanceModel, DId};
+
+/// How many instances have an optional boolean property set, not set, or unknown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PropertyCounts {
+    pub yes: usize,
+    pub no: usize,
+    pub unknown: usize,
+}
+
+impl PropertyCounts {
+    fn record(&mut self, value: Option<bool>) {
+        match value {
+            Some(true) => self.yes += 1,
+            Some(false) => self.no += 1,
+            None => self.unknown += 1,
+        }
+    }
+}
+
+/// Distributions over the metadata of a set of instances
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceStats {
+    pub instances: usize,
+    /// Number of instances whose data is stored in the local instance database
+    pub cached: usize,
+
+    /// Number of instances per size bucket; see [`size_bucket`]
+    pub node_buckets: BTreeMap<u32, usize>,
+    pub edge_buckets: BTreeMap<u32, usize>,
+
+    pub best_score_known: usize,
+    pub treewidth_known: usize,
+    pub diameter_known: usize,
+
+    pub planar: PropertyCounts,
+    pub bipartite: PropertyCounts,
+}
+
+impl InstanceStats {
+    pub fn new<'a>(
+        instances: impl IntoIterator<Item = &'a InstanceModel>,
+        cached_dids: &HashSet<DId>,
+    ) -> Self {
+        let mut stats = Self::default();
+
+        for instance in instances {
+            stats.instances += 1;
+            stats.cached += cached_dids.contains(&instance.data_did) as usize;
+
+            *stats
+                .node_buckets
+                .entry(size_bucket(instance.nodes))
+                .or_default() += 1;
+            *stats
+                .edge_buckets
+                .entry(size_bucket(instance.edges))
+                .or_default() += 1;
+
+            stats.best_score_known += instance.best_score.is_some() as usize;
+            stats.treewidth_known += instance.treewidth.is_some() as usize;
+            stats.diameter_known += instance.diameter.is_some() as usize;
+
+            stats.planar.record(instance.planar);
+            stats.bipartite.record(instance.bipartite);
+        }
+
+        stats
+    }
+
+    /// Share of `count` among all instances in percent
+    pub fn percent(&self, count: usize) -> f64 {
+        if self.instances == 0 {
+            0.0
+        } else {
+            100.0 * count as f64 / self.instances as f64
+        }
+    }
+}
+
+/// Sizes are grouped by their order of magnitude: bucket `k` contains all sizes in `[10^k, 10^(k+1))`;
+/// a size of 0 belongs to bucket 0.
+pub fn size_bucket(size: u32) -> u32 {
+    size.checked_ilog10().unwrap_or(0)
+}
+
+/// Human readable range of the bucket, e.g. `10 - 99`
+pub fn size_bucket_label(bucket: u32) -> String {
+    let lower = if bucket == 0 { 0 } else { 10u64.pow(bucket) };
+    let upper = 10u64.pow(bucket + 1) - 1;
+    format!("{lower} - {upper}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::IId;
+
+    fn instance(id: u32, nodes: u32, edges: u32) -> InstanceModel {
+        InstanceModel {
+            iid: IId::new(id),
+            data_did: DId::new(id),
+            nodes,
+            edges,
+            best_score: None,
+            diameter: None,
+            treewidth: None,
+            planar: None,
+            bipartite: None,
+        }
+    }
+
+    #[test]
+    fn size_bucket() {
+        assert_eq!(super::size_bucket(0), 0);
+        assert_eq!(super::size_bucket(9), 0);
+        assert_eq!(super::size_bucket(10), 1);
+        assert_eq!(super::size_bucket(999), 2);
+        assert_eq!(super::size_bucket(1000), 3);
+
+        assert_eq!(size_bucket_label(0), "0 - 9");
+        assert_eq!(size_bucket_label(2), "100 - 999");
+    }
+
+    #[test]
+    fn stats() {
+        let instances = vec![
+            InstanceModel {
+                best_score: Some(3),
+                treewidth: Some(2),
+                planar: Some(true),
+                bipartite: Some(false),
+                ..instance(1, 5, 8)
+            },
+            InstanceModel {
+                best_score: Some(10),
+                diameter: Some(4),
+                planar: Some(false),
+                ..instance(2, 50, 120)
+            },
+            instance(3, 70, 1500),
+            instance(4, 2000, 1999),
+        ];
+        let cached: HashSet<_> = [DId::new(2), DId::new(4), DId::new(5)]
+            .into_iter()
+            .collect();
+
+        let stats = InstanceStats::new(&instances, &cached);
+
+        assert_eq!(stats.instances, 4);
+        assert_eq!(stats.cached, 2);
+        assert_eq!(
+            stats
+                .node_buckets
+                .iter()
+                .map(|(&k, &v)| (k, v))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 2), (3, 1)]
+        );
+        assert_eq!(
+            stats
+                .edge_buckets
+                .iter()
+                .map(|(&k, &v)| (k, v))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (2, 1), (3, 2)]
+        );
+        assert_eq!(stats.best_score_known, 2);
+        assert_eq!(stats.treewidth_known, 1);
+        assert_eq!(stats.diameter_known, 1);
+        assert_eq!(
+            stats.planar,
+            PropertyCounts {
+                yes: 1,
+                no: 1,
+                unknown: 2
+            }
+        );
+        assert_eq!(
+            stats.bipartite,
+            PropertyCounts {
+                yes: 0,
+                no: 1,
+                unknown: 3
+            }
+        );
+        assert_eq!(stats.percent(stats.cached), 50.0);
+    }
+}
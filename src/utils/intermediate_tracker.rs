@@ -0,0 +1,354 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::pace::{
+    graph::{CsrGraph, Node},
+    Solution,
+};
+
+/// Longer lines cannot be part of a valid solution; the remainder of such a line is skipped
+/// rather than buffered, since the solver may never terminate it
+const MAX_LINE_LENGTH: usize = 4096;
+
+/// An intermediate solution as observed by the runner
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntermediateSolution {
+    /// Time since the solver was started until the runner read the solution
+    pub elapsed: Duration,
+    pub score: u32,
+    pub feasible: bool,
+}
+
+/// Follows the file into which an anytime solver appends its intermediate solutions.
+///
+/// Each solution uses the output format of the solver (the cardinality followed by one node
+/// per line; comment and empty lines are ignored). Only complete lines are processed, so the
+/// solver may write a solution in several steps.
+pub struct IntermediateTracker {
+    path: PathBuf,
+    start: Instant,
+
+    offset: u64,
+    /// Incomplete last line of the previous read
+    partial_line: Vec<u8>,
+    /// The incomplete last line exceeded `MAX_LINE_LENGTH`; everything up to the next line break is ignored
+    skip_line: bool,
+    /// Expected cardinality and nodes read so far of a solution not yet complete
+    pending: Option<(usize, Vec<Node>)>,
+
+    trace: Vec<IntermediateSolution>,
+    /// Time at which the smallest feasible solution was read, and its 1-indexed nodes
+    best: Option<(Duration, Vec<Node>)>,
+}
+
+impl IntermediateTracker {
+    pub fn new(path: PathBuf, start: Instant) -> Self {
+        Self {
+            path,
+            start,
+            offset: 0,
+            partial_line: Vec::new(),
+            skip_line: false,
+            pending: None,
+            trace: Vec::new(),
+            best: None,
+        }
+    }
+
+    /// Reads everything appended to the file since the last call; a missing file is not an error,
+    /// since the solver may not have written a solution yet
+    pub fn poll(&mut self, graph: &CsrGraph) -> std::io::Result<()> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut data = std::mem::take(&mut self.partial_line);
+        self.offset += file.read_to_end(&mut data)? as u64;
+
+        if self.skip_line {
+            match data.iter().position(|&b| b == b'\n') {
+                Some(pos) => {
+                    data.drain(..=pos);
+                    self.skip_line = false;
+                }
+                None => return Ok(()),
+            }
+        }
+
+        let complete = data
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |pos| pos + 1);
+        self.partial_line = data.split_off(complete);
+
+        let elapsed = self.start.elapsed();
+        for line in String::from_utf8_lossy(&data).lines() {
+            self.process_line(line.trim(), elapsed, graph);
+        }
+
+        if self.partial_line.len() > MAX_LINE_LENGTH {
+            debug!("Skip overlong line in {:?}", self.path);
+            self.partial_line = Vec::new();
+            self.skip_line = true;
+            self.pending = None;
+        }
+
+        Ok(())
+    }
+
+    fn process_line(&mut self, line: &str, elapsed: Duration, graph: &CsrGraph) {
+        if line.is_empty() || line.starts_with('c') {
+            return;
+        }
+
+        let Ok(value) = line.parse::<Node>() else {
+            debug!("Skip malformed line {line:?} in {:?}", self.path);
+            self.pending = None;
+            return;
+        };
+
+        let (expected, nodes) = match self.pending.take() {
+            Some((expected, mut nodes)) => {
+                nodes.push(value);
+                (expected, nodes)
+            }
+            None if value > graph.number_of_nodes() => {
+                debug!("Skip solution with cardinality {value} in {:?}", self.path);
+                return;
+            }
+            None => (value as usize, Vec::with_capacity(value as usize)),
+        };
+
+        if nodes.len() < expected {
+            self.pending = Some((expected, nodes));
+            return;
+        }
+
+        self.record(nodes, elapsed, graph);
+    }
+
+    fn record(&mut self, mut nodes: Vec<Node>, elapsed: Duration, graph: &CsrGraph) {
+        // score by the declared cardinality; as for the final solution, duplicates are malformed
+        let score = nodes.len() as u32;
+        nodes.sort_unstable();
+        let has_duplicates = nodes.windows(2).any(|w| w[0] == w[1]);
+
+        let feasible = !has_duplicates
+            && Solution::from_1indexed_vec(nodes.clone(), None)
+                .is_ok_and(|solution| solution.valid_domset_for_graph(graph));
+
+        self.trace.push(IntermediateSolution {
+            elapsed,
+            score,
+            feasible,
+        });
+
+        if feasible
+            && self
+                .best
+                .as_ref()
+                .is_none_or(|(_, best)| nodes.len() < best.len())
+        {
+            self.best = Some((elapsed, nodes));
+        }
+    }
+
+    /// Time since the solver was started
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn trace(&self) -> &[IntermediateSolution] {
+        &self.trace
+    }
+
+    /// Returns the time at which the smallest feasible intermediate solution was read
+    /// and its 1-indexed nodes
+    pub fn take_best(&mut self) -> Option<(Duration, Vec<Node>)> {
+        self.best.take()
+    }
+
+    /// Writes the trace as CSV with the columns `time_sec,score,feasible`
+    pub fn write_trace<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "time_sec,score,feasible")?;
+        for entry in &self.trace {
+            writeln!(
+                writer,
+                "{},{},{}",
+                entry.elapsed.as_secs_f64(),
+                entry.score,
+                entry.feasible
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pace::graph::Edge;
+    use tempdir::TempDir;
+
+    // path 1 - 2 - 3 - 4 - 5
+    fn path_graph() -> CsrGraph {
+        let edges: Vec<Edge> = vec![Edge(0, 1), Edge(1, 2), Edge(2, 3), Edge(3, 4)];
+        CsrGraph::try_from_edges(5, &edges).unwrap()
+    }
+
+    fn append(path: &std::path::Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn incremental_reads() {
+        let dir = TempDir::new("intermediate_tracker").unwrap();
+        let path = dir.path().join("iid1.intermediate");
+        let graph = path_graph();
+
+        let mut tracker = IntermediateTracker::new(path.clone(), Instant::now());
+        tracker.poll(&graph).unwrap(); // file does not exist yet
+        assert!(tracker.trace().is_empty());
+
+        // all nodes; the last line is incomplete
+        append(&path, "c first\n5\n1\n2\n3\n4\n5");
+        tracker.poll(&graph).unwrap();
+        assert!(tracker.trace().is_empty());
+
+        // infeasible, then the first half of an optimal one
+        append(&path, "\n1\n3\n\n2\n2\n");
+        tracker.poll(&graph).unwrap();
+
+        let scores: Vec<_> = tracker
+            .trace()
+            .iter()
+            .map(|e| (e.score, e.feasible))
+            .collect();
+        assert_eq!(scores, vec![(5, true), (1, false)]);
+
+        append(&path, "5\n");
+        tracker.poll(&graph).unwrap();
+        let scores: Vec<_> = tracker
+            .trace()
+            .iter()
+            .map(|e| (e.score, e.feasible))
+            .collect();
+        assert_eq!(scores, vec![(5, true), (1, false), (2, true)]);
+        assert_eq!(
+            tracker.take_best().map(|(_, nodes)| nodes),
+            Some(vec![2, 5])
+        );
+    }
+
+    #[test]
+    fn cardinality_exceeds_nodes() {
+        let dir = TempDir::new("intermediate_tracker").unwrap();
+        let path = dir.path().join("iid1.intermediate");
+        let graph = path_graph();
+
+        // the nodes of the rejected solution are read as cardinalities, i.e., also rejected
+        append(&path, "4000000000\n1\n2\n3\n4\n5\n6\n2\n2\n5\n");
+        let mut tracker = IntermediateTracker::new(path, Instant::now());
+        tracker.poll(&graph).unwrap();
+
+        let scores: Vec<_> = tracker
+            .trace()
+            .iter()
+            .map(|e| (e.score, e.feasible))
+            .collect();
+        assert_eq!(scores, vec![(1, false), (3, false), (2, true)]);
+        assert_eq!(
+            tracker.take_best().map(|(_, nodes)| nodes),
+            Some(vec![2, 5])
+        );
+    }
+
+    #[test]
+    fn overlong_line() {
+        let dir = TempDir::new("intermediate_tracker").unwrap();
+        let path = dir.path().join("iid1.intermediate");
+        let graph = path_graph();
+
+        let mut tracker = IntermediateTracker::new(path.clone(), Instant::now());
+        append(&path, "2\n2\n");
+        append(&path, &"5".repeat(MAX_LINE_LENGTH + 1));
+        tracker.poll(&graph).unwrap();
+        assert!(tracker.partial_line.is_empty());
+
+        // the rest of the line is skipped, as is the pending solution
+        append(&path, &"5".repeat(MAX_LINE_LENGTH));
+        tracker.poll(&graph).unwrap();
+        assert!(tracker.partial_line.is_empty());
+
+        append(&path, "\n2\n2\n5\n");
+        tracker.poll(&graph).unwrap();
+
+        let scores: Vec<_> = tracker
+            .trace()
+            .iter()
+            .map(|e| (e.score, e.feasible))
+            .collect();
+        assert_eq!(scores, vec![(2, true)]);
+    }
+
+    #[test]
+    fn duplicated_node() {
+        let dir = TempDir::new("intermediate_tracker").unwrap();
+        let path = dir.path().join("iid1.intermediate");
+        let graph = path_graph();
+
+        // {2, 5} dominates the path, but listing 2 twice is malformed
+        append(&path, "3\n2\n5\n2\n");
+        let mut tracker = IntermediateTracker::new(path, Instant::now());
+        tracker.poll(&graph).unwrap();
+
+        let scores: Vec<_> = tracker
+            .trace()
+            .iter()
+            .map(|e| (e.score, e.feasible))
+            .collect();
+        assert_eq!(scores, vec![(3, false)]);
+        assert_eq!(tracker.take_best(), None);
+    }
+
+    #[test]
+    fn out_of_range_and_malformed() {
+        let dir = TempDir::new("intermediate_tracker").unwrap();
+        let path = dir.path().join("iid1.intermediate");
+        let graph = path_graph();
+
+        append(&path, "2\n2\n7\n2\nfoo\n1\n3\n");
+        let mut tracker = IntermediateTracker::new(path, Instant::now());
+        tracker.poll(&graph).unwrap();
+
+        // the malformed line discards the pending solution
+        let scores: Vec<_> = tracker
+            .trace()
+            .iter()
+            .map(|e| (e.score, e.feasible))
+            .collect();
+        assert_eq!(scores, vec![(2, false), (1, false)]);
+        assert_eq!(tracker.take_best(), None);
+
+        let mut buffer = Vec::new();
+        tracker.write_trace(&mut buffer).unwrap();
+        let csv = String::from_utf8(buffer).unwrap();
+        assert!(csv.starts_with("time_sec,score,feasible\n"));
+        assert!(csv.ends_with(",1,false\n"));
+    }
+}
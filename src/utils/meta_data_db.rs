@@ -1,16 +1,17 @@
 use anyhow::Context;
 use rusqlite::{Connection, OpenFlags};
-use std::path::Path;
+use serde::Serialize;
+use std::{collections::HashMap, path::Path};
 use tokio::sync::Mutex;
 use tracing::trace;
 
-use super::{DId, IId};
+use super::{instance_filter::InstanceFilter, DId, IId};
 
 pub struct MetaDataDB {
     meta_db: Mutex<Connection>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct InstanceModel {
     pub iid: IId,
     pub data_did: DId,
@@ -25,6 +26,23 @@ pub struct InstanceModel {
 
 pub struct DangerousRawClause<'a>(pub &'a str);
 
+const INSTANCE_COLUMNS: &str =
+    "iid, data_did, nodes, edges, best_score, diameter, treewidth, planar, bipartite";
+
+fn instance_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<InstanceModel> {
+    Ok(InstanceModel {
+        iid: IId::new(row.get(0)?),
+        data_did: DId::new(row.get(1)?),
+        nodes: row.get(2)?,
+        edges: row.get(3)?,
+        best_score: row.get(4)?,
+        diameter: row.get(5)?,
+        treewidth: row.get(6)?,
+        planar: row.get(7)?,
+        bipartite: row.get(8)?,
+    })
+}
+
 impl MetaDataDB {
     pub async fn new(db_path: &Path) -> anyhow::Result<Self> {
         let meta_db = Self::open_db_pool(db_path).await?;
@@ -47,24 +65,27 @@ impl MetaDataDB {
         trace!("Starting fetch_instance");
 
         let conn = self.meta_db.lock().await;
-        let mut stmt = conn.prepare_cached(
-            r"SELECT iid, data_did, nodes, edges, best_score, diameter, treewidth, planar, bipartite FROM Instance WHERE iid = ?1",
-        )?;
-
-        stmt.query_row([iid.iid_to_u32()], |row| {
-            Ok(InstanceModel {
-                iid: IId::new(row.get(0)?),
-                data_did: DId::new(row.get(1)?),
-                nodes: row.get(2)?,
-                edges: row.get(3)?,
-                best_score: row.get(4)?,
-                diameter: row.get(5)?,
-                treewidth: row.get(6)?,
-                planar: row.get(7)?,
-                bipartite: row.get(8)?,
-            })
-        })
-        .with_context(|| format!("Fetching instance info for {iid:?}"))
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {INSTANCE_COLUMNS} FROM Instance WHERE iid = ?1"
+        ))?;
+
+        stmt.query_row([iid.iid_to_u32()], instance_from_row)
+            .with_context(|| format!("Fetching instance info for {iid:?}"))
+    }
+
+    /// Returns the metadata of all instances in the database
+    pub async fn fetch_all_instances(&self) -> anyhow::Result<Vec<InstanceModel>> {
+        trace!("Starting fetch_all_instances");
+
+        let conn = self.meta_db.lock().await;
+        let mut stmt = conn.prepare_cached(&format!("SELECT {INSTANCE_COLUMNS} FROM Instance"))?;
+
+        let instances = stmt
+            .query_map([], instance_from_row)?
+            .collect::<Result<_, _>>()
+            .context("Fetching metadata of all instances")?;
+
+        Ok(instances)
     }
 
     /// there might be some "security" implications here, but I do not really care:
@@ -93,6 +114,46 @@ impl MetaDataDB {
         Ok(iids)
     }
 
+    pub async fn fetch_instance_iids_by_filter(
+        &self,
+        filter: &InstanceFilter,
+    ) -> anyhow::Result<Vec<IId>> {
+        trace!("Starting fetch_instance_iids_by_filter");
+
+        let (where_clause, params) = filter.to_sql();
+        let sql = format!("SELECT iid FROM Instance WHERE {where_clause}");
+
+        let conn = self.meta_db.lock().await;
+        let mut stmt = conn
+            .prepare_cached(&sql)
+            .with_context(|| format!("Preparing statement for {sql}"))?;
+
+        let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+
+        let mut iids = Vec::new();
+        while let Some(row) = rows.next()? {
+            iids.push(IId::new(row.get(0)?));
+        }
+
+        Ok(iids)
+    }
+
+    /// Returns the number of nodes and edges of all instances
+    pub async fn fetch_instance_sizes(&self) -> anyhow::Result<HashMap<IId, (u32, u32)>> {
+        trace!("Starting fetch_instance_sizes");
+
+        let conn = self.meta_db.lock().await;
+        let mut stmt = conn.prepare_cached("SELECT iid, nodes, edges FROM Instance")?;
+        let mut rows = stmt.query([])?;
+
+        let mut sizes = HashMap::new();
+        while let Some(row) = rows.next()? {
+            sizes.insert(IId::new(row.get(0)?), (row.get(1)?, row.get(2)?));
+        }
+
+        Ok(sizes)
+    }
+
     async fn open_db_pool(path: &Path) -> anyhow::Result<Connection> {
         trace!("Starting open_db_pool");
         if !path.is_file() {
@@ -1,12 +1,19 @@
 pub mod directory;
 pub mod download_progress_bar;
+pub mod event_logger;
 pub mod ids;
 pub mod instance_data_db;
+pub mod instance_filter;
+pub mod instance_stats;
+pub mod intermediate_tracker;
 pub mod meta_data_db;
+pub mod run_comparison;
 pub mod run_summary_logger;
 pub mod server_connection;
 pub mod settings;
+pub mod solution_cache_db;
 pub mod solution_upload;
 pub mod solver_executor;
+pub mod upload_queue;
 
 pub use ids::{DId, IId};
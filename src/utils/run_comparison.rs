@@ -0,0 +1,330 @@
+use std::{collections::BTreeMap, fmt::Display, io::Write};
+
+use serde::Serialize;
+
+use super::{
+    run_summary_logger::{escape_csv_field, RunSummaryEntry},
+    IId,
+};
+
+/// How the result of an instance changed from run A to run B
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Better,
+    Worse,
+    NewlySucceeded,
+    NewlyFailed,
+    Unchanged,
+    BothFailed,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Better => "better",
+            Self::Worse => "worse",
+            Self::NewlySucceeded => "newly_succeeded",
+            Self::NewlyFailed => "newly_failed",
+            Self::Unchanged => "unchanged",
+            Self::BothFailed => "both_failed",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InstanceComparison {
+    pub iid: IId,
+    /// Repetitions of the same instance (see `run --repetitions`) are compared pairwise
+    pub repetition: u32,
+    pub change: Change,
+    pub state_a: String,
+    pub state_b: String,
+    pub score_a: Option<u32>,
+    pub score_b: Option<u32>,
+    /// `score_b - score_a`; negative values are improvements
+    pub score_delta: Option<i64>,
+    pub time_sec_a: f64,
+    pub time_sec_b: f64,
+    /// `time_sec_b / time_sec_a`; values below 1 indicate that run B was faster
+    pub time_ratio: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ComparisonStats {
+    pub common: usize,
+    pub only_in_a: usize,
+    pub only_in_b: usize,
+
+    pub better: usize,
+    pub worse: usize,
+    pub newly_succeeded: usize,
+    pub newly_failed: usize,
+    pub unchanged: usize,
+    pub both_failed: usize,
+
+    /// Sums over all instances solved in both runs
+    pub score_sum_a: u64,
+    pub score_sum_b: u64,
+
+    /// Sums over all instances in both runs
+    pub time_sec_sum_a: f64,
+    pub time_sec_sum_b: f64,
+
+    /// Geometric mean of the time ratios of all instances solved in both runs
+    pub time_ratio_geo_mean: Option<f64>,
+}
+
+/// Two run summaries joined on their IIDs and repetitions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunComparison {
+    pub stats: ComparisonStats,
+    pub instances: Vec<InstanceComparison>,
+}
+
+impl RunComparison {
+    /// Entries are matched by their IID and repetition. If such a pair occurs multiple times in
+    /// a summary (e.g., in a resumed run), the last entry is used.
+    pub fn new(run_a: &[RunSummaryEntry], run_b: &[RunSummaryEntry]) -> Self {
+        let run_a = by_job(run_a);
+        let run_b = by_job(run_b);
+
+        let instances: Vec<_> = run_a
+            .iter()
+            .filter_map(|(job, a)| run_b.get(job).map(|b| compare_instance(a, b)))
+            .collect();
+
+        let mut stats = ComparisonStats {
+            common: instances.len(),
+            only_in_a: run_a.len() - instances.len(),
+            only_in_b: run_b.len() - instances.len(),
+            ..Default::default()
+        };
+
+        let mut log_ratio_sum = 0.0;
+        let mut num_ratios = 0;
+        for inst in &instances {
+            match inst.change {
+                Change::Better => stats.better += 1,
+                Change::Worse => stats.worse += 1,
+                Change::NewlySucceeded => stats.newly_succeeded += 1,
+                Change::NewlyFailed => stats.newly_failed += 1,
+                Change::Unchanged => stats.unchanged += 1,
+                Change::BothFailed => stats.both_failed += 1,
+            }
+
+            stats.time_sec_sum_a += inst.time_sec_a;
+            stats.time_sec_sum_b += inst.time_sec_b;
+
+            if let (Some(a), Some(b)) = (inst.score_a, inst.score_b) {
+                stats.score_sum_a += a as u64;
+                stats.score_sum_b += b as u64;
+
+                if let Some(ratio) = inst.time_ratio.filter(|r| *r > 0.0) {
+                    log_ratio_sum += ratio.ln();
+                    num_ratios += 1;
+                }
+            }
+        }
+
+        if num_ratios > 0 {
+            stats.time_ratio_geo_mean = Some((log_ratio_sum / num_ratios as f64).exp());
+        }
+
+        Self { stats, instances }
+    }
+
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> anyhow::Result<()> {
+        fn opt<T: ToString>(x: Option<T>) -> String {
+            x.map_or_else(String::new, |x| x.to_string())
+        }
+
+        writeln!(
+            writer,
+            "iid,repetition,change,state_a,state_b,score_a,score_b,score_delta,time_sec_a,time_sec_b,time_ratio"
+        )?;
+
+        for inst in &self.instances {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                inst.iid.iid_to_u32(),
+                inst.repetition,
+                inst.change,
+                escape_csv_field(&inst.state_a),
+                escape_csv_field(&inst.state_b),
+                opt(inst.score_a),
+                opt(inst.score_b),
+                opt(inst.score_delta),
+                inst.time_sec_a,
+                inst.time_sec_b,
+                opt(inst.time_ratio),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn by_job(run: &[RunSummaryEntry]) -> BTreeMap<(IId, u32), &RunSummaryEntry> {
+    run.iter().map(|e| ((e.iid, e.repetition), e)).collect()
+}
+
+fn compare_instance(a: &RunSummaryEntry, b: &RunSummaryEntry) -> InstanceComparison {
+    let change = match (a.score, b.score) {
+        (Some(a), Some(b)) if b < a => Change::Better,
+        (Some(a), Some(b)) if b > a => Change::Worse,
+        (Some(_), Some(_)) => Change::Unchanged,
+        (None, Some(_)) => Change::NewlySucceeded,
+        (Some(_), None) => Change::NewlyFailed,
+        (None, None) => Change::BothFailed,
+    };
+
+    InstanceComparison {
+        iid: a.iid,
+        repetition: a.repetition,
+        change,
+        state_a: a.state.clone(),
+        state_b: b.state.clone(),
+        score_a: a.score,
+        score_b: b.score,
+        score_delta: a.score.zip(b.score).map(|(a, b)| b as i64 - a as i64),
+        time_sec_a: a.time_sec,
+        time_sec_b: b.time_sec,
+        time_ratio: (a.time_sec > 0.0).then(|| b.time_sec / a.time_sec),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(iid: u32, time_sec: f64, score: Option<u32>) -> RunSummaryEntry {
+        RunSummaryEntry {
+            iid: IId::new(iid),
+            repetition: 0,
+            time_sec,
+            state: if score.is_some() { "best" } else { "timeout" }.into(),
+            score,
+        }
+    }
+
+    #[test]
+    fn changes() {
+        let run_a = [
+            entry(1, 1.0, Some(10)),
+            entry(2, 1.0, Some(10)),
+            entry(3, 1.0, Some(10)),
+            entry(4, 1.0, None),
+            entry(5, 1.0, Some(10)),
+            entry(6, 1.0, None),
+            entry(7, 1.0, None),
+        ];
+
+        let run_b = [
+            entry(5, 1.0, None),
+            entry(4, 1.0, Some(10)),
+            entry(3, 1.0, Some(10)),
+            entry(2, 1.0, Some(11)),
+            entry(1, 1.0, Some(9)),
+            entry(6, 1.0, None),
+            entry(8, 1.0, None),
+            entry(9, 1.0, None),
+        ];
+
+        let comparison = RunComparison::new(&run_a, &run_b);
+        assert_eq!(
+            comparison
+                .instances
+                .iter()
+                .map(|i| (i.iid.iid_to_u32(), i.change))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, Change::Better),
+                (2, Change::Worse),
+                (3, Change::Unchanged),
+                (4, Change::NewlySucceeded),
+                (5, Change::NewlyFailed),
+                (6, Change::BothFailed),
+            ]
+        );
+
+        let stats = &comparison.stats;
+        assert_eq!(stats.common, 6);
+        assert_eq!(stats.only_in_a, 1);
+        assert_eq!(stats.only_in_b, 2);
+        assert_eq!((stats.better, stats.worse, stats.unchanged), (1, 1, 1),);
+        assert_eq!(
+            (stats.newly_succeeded, stats.newly_failed, stats.both_failed),
+            (1, 1, 1)
+        );
+        assert_eq!((stats.score_sum_a, stats.score_sum_b), (30, 30));
+    }
+
+    #[test]
+    fn deltas_and_ratios() {
+        let comparison = RunComparison::new(
+            &[entry(1, 2.0, Some(10)), entry(2, 1.0, Some(5))],
+            &[entry(1, 1.0, Some(8)), entry(2, 4.0, Some(5))],
+        );
+
+        assert_eq!(comparison.instances[0].score_delta, Some(-2));
+        assert_eq!(comparison.instances[0].time_ratio, Some(0.5));
+        assert_eq!(comparison.instances[1].score_delta, Some(0));
+        assert_eq!(comparison.instances[1].time_ratio, Some(4.0));
+
+        // sqrt(0.5 * 4)
+        let geo_mean = comparison.stats.time_ratio_geo_mean.unwrap();
+        assert!((geo_mean - 2.0f64.sqrt()).abs() < 1e-9);
+        assert_eq!(comparison.stats.time_sec_sum_a, 3.0);
+        assert_eq!(comparison.stats.time_sec_sum_b, 5.0);
+    }
+
+    #[test]
+    fn last_entry_wins() {
+        let comparison = RunComparison::new(
+            &[entry(1, 1.0, None), entry(1, 1.0, Some(3))],
+            &[entry(1, 1.0, Some(3))],
+        );
+
+        assert_eq!(comparison.stats.common, 1);
+        assert_eq!(comparison.instances[0].change, Change::Unchanged);
+    }
+
+    #[test]
+    fn repetitions() {
+        let rep = |iid, repetition, score| RunSummaryEntry {
+            repetition,
+            ..entry(iid, 1.0, score)
+        };
+
+        let comparison = RunComparison::new(
+            &[rep(1, 0, Some(5)), rep(1, 1, Some(6)), rep(1, 2, Some(5))],
+            &[rep(1, 1, Some(5)), rep(1, 0, Some(5))],
+        );
+
+        assert_eq!(
+            comparison
+                .instances
+                .iter()
+                .map(|i| (i.repetition, i.change))
+                .collect::<Vec<_>>(),
+            vec![(0, Change::Unchanged), (1, Change::Better)]
+        );
+        assert_eq!(comparison.stats.common, 2);
+        assert_eq!(comparison.stats.only_in_a, 1);
+    }
+
+    #[test]
+    fn csv() {
+        let comparison = RunComparison::new(&[entry(1, 2.0, Some(10))], &[entry(1, 1.0, None)]);
+
+        let mut buffer = Vec::new();
+        comparison.write_csv(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "iid,repetition,change,state_a,state_b,score_a,score_b,score_delta,time_sec_a,time_sec_b,time_ratio\n\
+            1,0,newly_failed,best,timeout,10,,,2,1,0.5\n"
+        );
+    }
+}
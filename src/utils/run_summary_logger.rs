@@ -1,7 +1,11 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::HashSet, path::Path, sync::Arc};
 
 use anyhow::Context;
-use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
 
 use crate::commands::run::job::JobResult;
 
@@ -12,7 +16,49 @@ pub struct RunSummaryLogger {
     file: Arc<Mutex<File>>,
 }
 
-const HEADER_STR: &str = "iid,time_sec,state,score,best_score_known\n";
+const HEADER_STR: &str =
+    "iid,time_sec,state,score,best_score_known,peak_rss_kb,user_time_sec,system_time_sec,details,repetition,seed,target_reached_sec\n";
+
+/// Quotes the field if it contains characters that have a special meaning in CSV
+pub fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Inverse of `escape_csv_field` applied to a whole line
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+/// A single line of a run summary as read back from disk
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummaryEntry {
+    pub iid: IId,
+    /// Zero for summaries written before `--repetitions` existed
+    pub repetition: u32,
+    pub time_sec: f64,
+    pub state: String,
+    pub score: Option<u32>,
+}
 
 impl RunSummaryLogger {
     pub async fn try_new(path: &Path) -> anyhow::Result<Self> {
@@ -27,7 +73,119 @@ impl RunSummaryLogger {
         })
     }
 
-    pub async fn log_job_result(&self, iid: IId, summary: &JobResult) -> anyhow::Result<()> {
+    /// Appends to an existing summary (e.g., when resuming a run); creates it if necessary.
+    /// Fails if the existing summary uses a different column layout, e.g., of an older version.
+    pub async fn try_append(path: &Path) -> anyhow::Result<Self> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("Failed to open run summary file at {path:?}"))?;
+
+        // a crash may leave an incomplete last line; it is dropped (see `read_entries`) and
+        // subsequent lines have to start on a fresh line
+        let content = tokio::fs::read(path).await?;
+        let complete = content
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |pos| pos + 1);
+
+        if complete > 0 && !content.starts_with(HEADER_STR.as_bytes()) {
+            let header = content.split(|&c| c == b'\n').next().unwrap_or_default();
+            anyhow::bail!(
+                "Run summary {path:?} has the header {:?}, but expected {:?}; \
+                it was probably written by a different version of the runner",
+                String::from_utf8_lossy(header),
+                HEADER_STR.trim_end()
+            );
+        }
+
+        if complete < content.len() {
+            file.set_len(complete as u64).await?;
+        }
+
+        if file.metadata().await?.len() == 0 {
+            file.write_all(HEADER_STR.as_bytes()).await?;
+        }
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Returns the (IID, repetition) pairs of all jobs recorded in an existing summary,
+    /// except for interrupted ones
+    pub fn read_logged_jobs(path: &Path) -> anyhow::Result<HashSet<(IId, u32)>> {
+        Ok(Self::read_entries(path)?
+            .into_iter()
+            .filter(|e| e.state != "interrupted")
+            .map(|e| (e.iid, e.repetition))
+            .collect())
+    }
+
+    /// Reads all entries of an existing summary in the order they were logged.
+    /// Columns are identified by the header, so summaries of older versions can be read as well.
+    /// A last line without a line break is ignored, since the runner may have crashed while writing it.
+    pub fn read_entries(path: &Path) -> anyhow::Result<Vec<RunSummaryEntry>> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to open run summary file at {path:?}"))?;
+        let complete = content.rfind('\n').map_or("", |pos| &content[..pos]);
+        let mut lines = complete.lines();
+
+        let header = match lines.next() {
+            Some(header) => split_csv_line(header),
+            None => return Ok(Vec::new()),
+        };
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|h| h == name)
+                .with_context(|| format!("Run summary {path:?} has no column {name}"))
+        };
+        let (col_iid, col_time, col_state, col_score) = (
+            column("iid")?,
+            column("time_sec")?,
+            column("state")?,
+            column("score")?,
+        );
+        let col_repetition = column("repetition").ok();
+
+        let mut entries = Vec::new();
+        for line in lines {
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields = split_csv_line(line);
+            let field = |idx: usize| fields.get(idx).map_or("", |f| f.as_str());
+            let context = || format!("Invalid run summary line {line:?}");
+
+            entries.push(RunSummaryEntry {
+                iid: field(col_iid).parse().with_context(context)?,
+                repetition: match col_repetition {
+                    Some(col) => field(col).parse().with_context(context)?,
+                    None => 0,
+                },
+                time_sec: field(col_time).parse().with_context(context)?,
+                state: field(col_state).to_string(),
+                score: match field(col_score) {
+                    "" => None,
+                    x => Some(x.parse().with_context(context)?),
+                },
+            });
+        }
+
+        Ok(entries)
+    }
+
+    pub async fn log_job_result(
+        &self,
+        iid: IId,
+        repetition: u32,
+        seed: u64,
+        summary: &JobResult,
+    ) -> anyhow::Result<()> {
         use crate::commands::run::job::JobResultState::*;
 
         let (score, best_known) = match summary.state {
@@ -36,13 +194,32 @@ impl RunSummaryLogger {
             _ => (None, None),
         };
 
+        let usage = summary.resource_usage.as_ref();
+
         let line = format!(
-            "{},{},{},{},{}\n",
+            "{},{},{},{},{},{},{},{},{},{},{},{}\n",
             iid.iid_to_u32(),
             summary.runtime.as_secs_f64(),
             summary.state,
             score.map_or_else(String::new, |s| s.to_string()),
             best_known.map_or_else(String::new, |s| s.to_string()),
+            usage.map_or_else(String::new, |u| u.peak_rss_kb.to_string()),
+            usage.map_or_else(String::new, |u| u.user_time.as_secs_f64().to_string()),
+            usage.map_or_else(String::new, |u| u.system_time.as_secs_f64().to_string()),
+            summary
+                .state
+                .details()
+                .or_else(|| {
+                    summary
+                        .from_intermediate
+                        .then(|| "intermediate solution".to_string())
+                })
+                .map_or_else(String::new, |d| escape_csv_field(&d)),
+            repetition,
+            seed,
+            summary
+                .target_reached
+                .map_or_else(String::new, |t| t.as_secs_f64().to_string()),
         );
 
         let mut file = self.file.lock().await;
@@ -60,7 +237,7 @@ impl RunSummaryLogger {
 
 #[cfg(test)]
 mod test {
-    use crate::commands::run::job::JobResultState;
+    use crate::{commands::run::job::JobResultState, utils::solver_executor::ResourceUsage};
     use tempdir::TempDir;
 
     use super::*;
@@ -76,9 +253,16 @@ mod test {
             let job_result = JobResult {
                 state: JobResultState::BestKnown { score: 42 },
                 runtime: std::time::Duration::from_secs(1),
+                resource_usage: Some(ResourceUsage {
+                    peak_rss_kb: 2048,
+                    user_time: std::time::Duration::from_millis(1500),
+                    system_time: std::time::Duration::from_millis(250),
+                }),
+                from_intermediate: false,
+                target_reached: None,
             };
             logger
-                .log_job_result(IId::new(1), &job_result)
+                .log_job_result(IId::new(1), 0, 7, &job_result)
                 .await
                 .unwrap();
         }
@@ -90,20 +274,44 @@ mod test {
                     best_known: 1024,
                 },
                 runtime: std::time::Duration::from_secs(4),
+                resource_usage: None,
+                from_intermediate: true,
+                target_reached: Some(std::time::Duration::from_millis(2500)),
             };
             logger
-                .log_job_result(IId::new(2), &job_result)
+                .log_job_result(IId::new(2), 1, 8, &job_result)
                 .await
                 .unwrap();
         }
 
         {
             let job_result = JobResult {
-                state: JobResultState::Error,
+                state: JobResultState::SyntaxError {
+                    message: "Invalid value, \"x\"".into(),
+                },
                 runtime: std::time::Duration::from_secs(2),
+                resource_usage: None,
+                from_intermediate: false,
+                target_reached: None,
             };
             logger
-                .log_job_result(IId::new(3), &job_result)
+                .log_job_result(IId::new(3), 0, 7, &job_result)
+                .await
+                .unwrap();
+        }
+
+        {
+            let job_result = JobResult {
+                state: JobResultState::Crashed {
+                    signal: libc::SIGSEGV,
+                },
+                runtime: std::time::Duration::from_secs(3),
+                resource_usage: None,
+                from_intermediate: false,
+                target_reached: None,
+            };
+            logger
+                .log_job_result(IId::new(4), 0, 7, &job_result)
                 .await
                 .unwrap();
         }
@@ -111,7 +319,147 @@ mod test {
         let content = tokio::fs::read_to_string(&path).await.unwrap();
         assert_eq!(
             content,
-            "iid,time_sec,state,score,best_score_known\n1,1,best,42,42\n2,4,suboptimal,1337,1024\n3,2,error,,\n"
+            "iid,time_sec,state,score,best_score_known,peak_rss_kb,user_time_sec,system_time_sec,details,repetition,seed,target_reached_sec\n\
+            1,1,best,42,42,2048,1.5,0.25,,0,7,\n\
+            2,4,suboptimal,1337,1024,,,,intermediate solution,1,8,2.5\n\
+            3,2,syntax_error,,,,,,\"Invalid value, \"\"x\"\"\",0,7,\n\
+            4,3,crashed,,,,,,SIGSEGV (11),0,7,\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn append_and_read_logged_jobs() {
+        let dir = TempDir::new("run_summary_logger").unwrap();
+        let path = dir.path().join("summary.csv");
+
+        for (iid, repetition, state) in [
+            (3, 0, JobResultState::Timeout),
+            (2, 0, JobResultState::Interrupted),
+            (1, 0, JobResultState::Timeout),
+            (1, 1, JobResultState::Timeout),
+        ] {
+            let logger = RunSummaryLogger::try_append(&path).await.unwrap();
+            let job_result = JobResult {
+                state,
+                runtime: std::time::Duration::from_secs(1),
+                resource_usage: None,
+                from_intermediate: false,
+                target_reached: None,
+            };
+            logger
+                .log_job_result(IId::new(iid), repetition, 0, &job_result)
+                .await
+                .unwrap();
+        }
+
+        // header is only written once
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(content.matches("iid,").count(), 1);
+
+        let jobs = RunSummaryLogger::read_logged_jobs(&path).unwrap();
+        assert_eq!(
+            jobs,
+            HashSet::from([(IId::new(1), 0), (IId::new(1), 1), (IId::new(3), 0)])
+        );
+    }
+
+    #[tokio::test]
+    async fn truncated_last_line() {
+        let dir = TempDir::new("run_summary_logger").unwrap();
+        let path = dir.path().join("summary.csv");
+
+        std::fs::write(
+            &path,
+            format!("{HEADER_STR}1,1.5,best,42,42,,,,,0,7,\n2,3.2,subopt"),
+        )
+        .unwrap();
+
+        let jobs = RunSummaryLogger::read_logged_jobs(&path).unwrap();
+        assert_eq!(jobs, HashSet::from([(IId::new(1), 0)]));
+
+        let logger = RunSummaryLogger::try_append(&path).await.unwrap();
+        let job_result = JobResult {
+            state: JobResultState::Timeout,
+            runtime: std::time::Duration::from_secs(1),
+            resource_usage: None,
+            from_intermediate: false,
+            target_reached: None,
+        };
+        logger
+            .log_job_result(IId::new(2), 0, 7, &job_result)
+            .await
+            .unwrap();
+
+        let entries = RunSummaryLogger::read_entries(&path).unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| (e.iid, e.state.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(IId::new(1), "best"), (IId::new(2), "timeout")]
+        );
+    }
+
+    #[tokio::test]
+    async fn append_with_different_header() {
+        let dir = TempDir::new("run_summary_logger").unwrap();
+        let path = dir.path().join("summary.csv");
+
+        let old_summary = "iid,time_sec,state,score,best_score_known\n1,1.5,best,42,42\n";
+        std::fs::write(&path, old_summary).unwrap();
+
+        let error = RunSummaryLogger::try_append(&path)
+            .await
+            .err()
+            .expect("header mismatch must be rejected");
+        assert!(error.to_string().contains("iid,time_sec,state"));
+
+        // the summary is left untouched
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), old_summary);
+    }
+
+    #[test]
+    fn split_csv_line() {
+        assert_eq!(super::split_csv_line("a,,b"), vec!["a", "", "b"]);
+        assert_eq!(
+            super::split_csv_line(&format!("1,{}", escape_csv_field("x, \"y\""))),
+            vec!["1", "x, \"y\""]
+        );
+    }
+
+    #[test]
+    fn read_entries() {
+        let dir = TempDir::new("run_summary_logger").unwrap();
+        let path = dir.path().join("summary.csv");
+
+        // summaries written before resource usage was recorded lack some columns
+        std::fs::write(
+            &path,
+            "iid,time_sec,state,score,best_score_known\n\
+            1,1.5,best,42,42\n\
+            2,300,timeout,,\n",
+        )
+        .unwrap();
+
+        let entries = RunSummaryLogger::read_entries(&path).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                RunSummaryEntry {
+                    iid: IId::new(1),
+                    repetition: 0,
+                    time_sec: 1.5,
+                    state: "best".into(),
+                    score: Some(42),
+                },
+                RunSummaryEntry {
+                    iid: IId::new(2),
+                    repetition: 0,
+                    time_sec: 300.0,
+                    state: "timeout".into(),
+                    score: None,
+                }
+            ]
         );
     }
 }
@@ -1,17 +1,108 @@
 use futures_util::StreamExt;
-use reqwest::{Client, ClientBuilder, Url};
+use reqwest::{Client, ClientBuilder, Proxy, StatusCode, Url};
 use std::sync::Arc;
-use std::{cmp::min, fs::File, io::Write, path::Path, time::Instant};
+use std::{
+    cmp::min,
+    fs::File,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
 use tracing::debug;
 use uuid::Uuid;
 
 use crate::commands::arguments::CommonOpts;
 
 pub const DEFAULT_SERVER_URL: &str = "https://domset.algorithm.engineering";
+pub const DEFAULT_CONNECT_TIMEOUT_SEC: u64 = 10;
+pub const DEFAULT_READ_TIMEOUT_SEC: u64 = 60;
+pub const DEFAULT_RETRIES: u32 = 3;
+
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Configuration of the HTTP client; timeouts of zero are disabled
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpOptions {
+    pub connect_timeout: Duration,
+    /// Maximum time without receiving data; aborts stalled downloads
+    pub read_timeout: Duration,
+    /// Number of retries of idempotent requests (GET) after transient failures
+    pub retries: u32,
+    /// Delay before the first retry; it doubles after each further attempt
+    pub retry_delay: Duration,
+    pub proxy: Option<Url>,
+    pub accept_invalid_certs: bool,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SEC),
+            read_timeout: Duration::from_secs(DEFAULT_READ_TIMEOUT_SEC),
+            retries: DEFAULT_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            proxy: None,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+impl HttpOptions {
+    pub fn from_opts(opts: &CommonOpts) -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(opts.connect_timeout),
+            read_timeout: Duration::from_secs(opts.read_timeout),
+            retries: opts.retries,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            proxy: opts.proxy.clone(),
+            accept_invalid_certs: opts.no_verify_tls,
+        }
+    }
+}
 
 pub struct ServerConnection {
     client: Arc<Client>,
     base_url: Url,
+    offline: bool,
+    retries: u32,
+    retry_delay: Duration,
+}
+
+/// Failures worth retrying: connection problems, timeouts, and overloaded servers.
+/// Other request errors (e.g., an invalid URL) would fail again.
+fn is_transient(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|e| e.downcast_ref::<reqwest::Error>())
+        .any(|e| match e.status() {
+            Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+            None => e.is_timeout() || e.is_connect(),
+        })
+}
+
+/// Tracks the remaining retries of a request; the delay doubles after each attempt
+struct Retries {
+    remaining: u32,
+    delay: Duration,
+}
+
+impl Retries {
+    /// Returns false if the request should not be repeated; otherwise waits before returning true
+    async fn wait_before_retry(&mut self, error: &anyhow::Error) -> bool {
+        if self.remaining == 0 || !is_transient(error) {
+            return false;
+        }
+
+        debug!(
+            "Request failed: {error:#}; retrying in {:?} ({} retries left)",
+            self.delay, self.remaining
+        );
+        tokio::time::sleep(self.delay).await;
+
+        self.remaining -= 1;
+        self.delay *= 2;
+        true
+    }
 }
 
 pub struct DownloadProgress {
@@ -37,23 +128,58 @@ impl ServerConnection {
     }
 
     pub fn new_from_opts(opts: &CommonOpts) -> anyhow::Result<Self> {
-        Self::new(opts.server_url().clone())
+        let mut conn =
+            Self::new_with_options(opts.server_url().clone(), &HttpOptions::from_opts(opts))?;
+        conn.offline = opts.offline;
+        Ok(conn)
     }
 
     pub fn new(base_url: Url) -> anyhow::Result<Self> {
-        let client = Arc::new(
-            ClientBuilder::new()
-                .danger_accept_invalid_certs(true)
-                .build()?,
-        );
+        Self::new_with_options(base_url, &HttpOptions::default())
+    }
+
+    pub fn new_with_options(base_url: Url, options: &HttpOptions) -> anyhow::Result<Self> {
+        let mut builder =
+            ClientBuilder::new().danger_accept_invalid_certs(options.accept_invalid_certs);
+
+        if !options.connect_timeout.is_zero() {
+            builder = builder.connect_timeout(options.connect_timeout);
+        }
+        if !options.read_timeout.is_zero() {
+            builder = builder.read_timeout(options.read_timeout);
+        }
+        if let Some(proxy) = &options.proxy {
+            builder = builder.proxy(Proxy::all(proxy.clone())?);
+        }
 
-        Ok(Self { client, base_url })
+        Ok(Self {
+            client: Arc::new(builder.build()?),
+            base_url,
+            offline: false,
+            retries: options.retries,
+            retry_delay: options.retry_delay,
+        })
     }
 
     pub fn base_url(&self) -> &Url {
         &self.base_url
     }
 
+    /// In offline mode (`--offline`), the server must never be contacted
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Fails with an error mentioning `action` in offline mode; call before each network access
+    pub fn ensure_online(&self, action: &str) -> anyhow::Result<()> {
+        if self.offline {
+            anyhow::bail!(
+                "Cannot {action} in offline mode (enabled by --offline or the config file)"
+            );
+        }
+        Ok(())
+    }
+
     pub fn solver_website_for_user(&self, uuid: Uuid) -> Url {
         let path = format!("runs.html?solver={uuid}");
         self.base_url.join(&path).unwrap()
@@ -63,6 +189,31 @@ impl ServerConnection {
         self.client.clone()
     }
 
+    fn retries(&self) -> Retries {
+        Retries {
+            remaining: self.retries,
+            delay: self.retry_delay,
+        }
+    }
+
+    /// Fetches the body of `url` as text; transient failures are retried
+    pub async fn get_text(&self, url: Url) -> anyhow::Result<String> {
+        let mut retries = self.retries();
+        loop {
+            let result = async {
+                let resp = self.client.get(url.clone()).send().await?;
+                resp.error_for_status_ref()?;
+                Ok(resp.text().await?)
+            }
+            .await;
+
+            match result {
+                Err(e) if retries.wait_before_retry(&e).await => continue,
+                result => return result,
+            }
+        }
+    }
+
     pub async fn download_file_with_updates<C: DownloadProgressCallback>(
         &self,
         url_without_host: &str,
@@ -70,6 +221,24 @@ impl ServerConnection {
         callback: &mut C,
     ) -> anyhow::Result<()> {
         let from_url = self.base_url.join(url_without_host)?;
+        self.ensure_online(&format!("download {from_url}"))?;
+
+        // a failed download starts over, since the file is truncated
+        let mut retries = self.retries();
+        loop {
+            match self.try_download(&from_url, to_path, callback).await {
+                Err(e) if retries.wait_before_retry(&e).await => continue,
+                result => return result,
+            }
+        }
+    }
+
+    async fn try_download<C: DownloadProgressCallback>(
+        &self,
+        from_url: &Url,
+        to_path: &Path,
+        callback: &mut C,
+    ) -> anyhow::Result<()> {
         debug!("Downloading {} to {:?}", from_url, to_path);
 
         let res = self.client.get(from_url.as_str()).send().await?;
@@ -194,6 +363,88 @@ mod test {
         assert!(callback.updated);
     }
 
+    fn common_opts() -> CommonOpts {
+        CommonOpts {
+            logging: None,
+            run_log_dir: "stride-logs".into(),
+            server_url: Url::parse(DEFAULT_SERVER_URL).unwrap(),
+            offline: false,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT_SEC,
+            read_timeout: DEFAULT_READ_TIMEOUT_SEC,
+            retries: DEFAULT_RETRIES,
+            proxy: None,
+            no_verify_tls: false,
+        }
+    }
+
+    #[test]
+    fn http_options_from_opts() {
+        assert_eq!(
+            HttpOptions::from_opts(&common_opts()),
+            HttpOptions::default()
+        );
+
+        let opts = CommonOpts {
+            connect_timeout: 0,
+            read_timeout: 5,
+            retries: 0,
+            proxy: Some(Url::parse("http://127.0.0.1:3128").unwrap()),
+            no_verify_tls: true,
+            ..common_opts()
+        };
+        let options = HttpOptions::from_opts(&opts);
+        assert_eq!(options.read_timeout, Duration::from_secs(5));
+        assert!(options.accept_invalid_certs);
+
+        // zero disables the timeout
+        let conn = ServerConnection::new_with_options(opts.server_url.clone(), &options).unwrap();
+        assert_eq!(conn.retries, 0);
+    }
+
+    #[tokio::test]
+    async fn retries() {
+        // nothing listens on port 1; connection errors are transient
+        let options = HttpOptions {
+            retries: 2,
+            retry_delay: Duration::from_millis(10),
+            ..HttpOptions::default()
+        };
+        let conn =
+            ServerConnection::new_with_options("http://127.0.0.1:1".parse().unwrap(), &options)
+                .unwrap();
+
+        let start = Instant::now();
+        let url = conn.base_url().join("api/status").unwrap();
+        assert!(conn.get_text(url).await.is_err());
+        // waited 10ms + 20ms before the two retries
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        // other errors are not retried
+        assert!(!is_transient(&anyhow::anyhow!("not a network problem")));
+        let invalid_url = conn.client.get("not a url").send().await.unwrap_err();
+        assert!(!is_transient(&invalid_url.into()));
+    }
+
+    #[tokio::test]
+    async fn offline() {
+        let opts = CommonOpts {
+            offline: true,
+            ..common_opts()
+        };
+        let conn = ServerConnection::new_from_opts(&opts).unwrap();
+        assert!(conn.is_offline());
+        assert!(conn.ensure_online("test").is_err());
+
+        let tmpfile = TempDir::new("download").unwrap();
+        let target = tmpfile.path().join("status.txt");
+        assert!(conn.download_file("api/status", &target).await.is_err());
+        assert!(!target.exists());
+
+        let conn = ServerConnection::try_default().unwrap();
+        assert!(!conn.is_offline());
+        assert!(conn.ensure_online("test").is_ok());
+    }
+
     #[tokio::test]
     async fn solver_website_for_user() {
         let conn = ServerConnection::try_default().unwrap();
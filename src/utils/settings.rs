@@ -6,7 +6,12 @@ use std::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{directory::StrideDirectory, server_connection::DEFAULT_SERVER_URL};
+use super::{
+    directory::StrideDirectory,
+    server_connection::{
+        DEFAULT_CONNECT_TIMEOUT_SEC, DEFAULT_READ_TIMEOUT_SEC, DEFAULT_RETRIES, DEFAULT_SERVER_URL,
+    },
+};
 
 static mut GLOBAL_SETTINGS: Option<Mutex<Settings>> = None;
 static GLOBAL_SETTINGS_INIT: Once = Once::new();
@@ -32,6 +37,32 @@ pub struct Settings {
     pub timeout: u64,
     pub grace: u64,
     pub parallel_jobs: usize,
+    /// Same as passing `--offline` to every command
+    #[serde(default)]
+    pub offline: bool,
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout: u64,
+    #[serde(default = "default_read_timeout")]
+    pub read_timeout: u64,
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Same as passing `--no-verify-tls` to every command
+    #[serde(default)]
+    pub no_verify_tls: bool,
+}
+
+fn default_connect_timeout() -> u64 {
+    DEFAULT_CONNECT_TIMEOUT_SEC
+}
+
+fn default_read_timeout() -> u64 {
+    DEFAULT_READ_TIMEOUT_SEC
+}
+
+fn default_retries() -> u32 {
+    DEFAULT_RETRIES
 }
 
 impl Default for Settings {
@@ -46,6 +77,12 @@ impl Default for Settings {
             timeout: 300,
             grace: 5,
             parallel_jobs: num_cpus::get(),
+            offline: false,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT_SEC,
+            read_timeout: DEFAULT_READ_TIMEOUT_SEC,
+            retries: DEFAULT_RETRIES,
+            proxy: None,
+            no_verify_tls: false,
         }
     }
 }
@@ -104,6 +141,29 @@ mod test {
         assert_eq!(settings, read_back);
     }
 
+    #[test]
+    fn load_without_optional_fields() {
+        let tmp_dir = TempDir::new("settings").unwrap();
+        let path = tmp_dir.path().join("settings.json");
+
+        // config files written by older versions lack these fields
+        let mut json = serde_json::to_value(Settings::default()).unwrap();
+        for field in [
+            "offline",
+            "connect_timeout",
+            "read_timeout",
+            "retries",
+            "proxy",
+            "no_verify_tls",
+        ] {
+            json.as_object_mut().unwrap().remove(field);
+        }
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let read_back = Settings::load_from_path(path.as_path()).unwrap();
+        assert_eq!(read_back, Settings::default());
+    }
+
     #[test]
     fn global_var() {
         let init = {
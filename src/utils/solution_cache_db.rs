@@ -0,0 +1,309 @@
+use anyhow::Context;
+use std::path::Path;
+use tokio::sync::Mutex;
+use tracing::{debug, trace};
+use uuid::Uuid;
+
+use crate::pace::Solution;
+
+use super::IId;
+
+use rusqlite::{Connection, OptionalExtension};
+
+/// Local cache of all feasible solutions verified by the runner
+pub struct SolutionCacheDB {
+    solution_cache_db: Mutex<Connection>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedSolution {
+    pub iid: IId,
+    pub solver_uuid: Option<Uuid>,
+    pub run_uuid: Uuid,
+    pub score: u32,
+    /// Solution in the PACE format (first line is the cardinality, one 1-indexed node per line)
+    pub data: String,
+}
+
+impl SolutionCacheDB {
+    pub async fn new(db_path: &Path) -> anyhow::Result<Self> {
+        let db = Self::connect_or_create_db(db_path).await?;
+        Ok(Self {
+            solution_cache_db: Mutex::new(db),
+        })
+    }
+
+    async fn connect_or_create_db(path: &Path) -> anyhow::Result<Connection> {
+        let already_exists = path.is_file();
+
+        if !already_exists {
+            debug!("Creating database {path:?}");
+        }
+        let connection = Connection::open(path)?;
+
+        trace!("Connection to SolutionCacheDB {path:?} is successful!");
+
+        if !already_exists {
+            debug!("Creating table `Solution` in database {path:?}");
+
+            connection.execute_batch(
+                "CREATE TABLE Solution (
+                    iid INT NOT NULL,
+                    solver_uuid TEXT NULL,
+                    run_uuid TEXT NOT NULL,
+                    score INT NOT NULL,
+                    digest BLOB NOT NULL,
+                    data BLOB NOT NULL,
+                    uploaded TINYINT NOT NULL,
+                    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+                );
+                CREATE INDEX SolutionByInstance ON Solution (iid, digest);",
+            )?;
+        }
+
+        Ok(connection)
+    }
+
+    /// Stores a feasible solution; `uploaded` indicates whether the server accepted it.
+    /// If the solution is already cached, only its `uploaded` flag may be set.
+    pub async fn insert(
+        &self,
+        iid: IId,
+        solver_uuid: Option<Uuid>,
+        run_uuid: Uuid,
+        solution: &Solution,
+        uploaded: bool,
+    ) -> anyhow::Result<()> {
+        let mut data = Vec::new();
+        solution.write(&mut data)?;
+
+        let digest = solution.compute_digest();
+        let conn = self.solution_cache_db.lock().await;
+
+        let updated = conn
+            .execute(
+                "UPDATE Solution SET uploaded = MAX(uploaded, ?3) WHERE iid = ?1 AND digest = ?2",
+                (iid.iid_to_u32(), digest.as_slice(), uploaded),
+            )
+            .with_context(|| format!("Updating cached solution for {iid:?}"))?;
+        if updated > 0 {
+            return Ok(());
+        }
+
+        conn.execute(
+            "INSERT INTO Solution (iid, solver_uuid, run_uuid, score, digest, data, uploaded) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                iid.iid_to_u32(),
+                solver_uuid.map(|u| u.to_string()),
+                run_uuid.to_string(),
+                solution.solution().len() as u32,
+                digest.as_slice(),
+                data,
+                uploaded,
+            ),
+        )
+        .with_context(|| format!("Inserting solution for {iid:?} into cache"))?;
+
+        Ok(())
+    }
+
+    /// Records that the server accepted the solution with the given digest
+    /// (see [`Solution::compute_digest`]), e.g., after sending a queued upload
+    pub async fn mark_uploaded(&self, iid: IId, digest: &[u8]) -> anyhow::Result<()> {
+        let conn = self.solution_cache_db.lock().await;
+        conn.execute(
+            "UPDATE Solution SET uploaded = 1 WHERE iid = ?1 AND digest = ?2",
+            (iid.iid_to_u32(), digest),
+        )
+        .with_context(|| format!("Marking solution for {iid:?} as uploaded"))?;
+
+        Ok(())
+    }
+
+    /// Returns true if an identical solution for the instance has already been uploaded
+    pub async fn was_uploaded(&self, iid: IId, solution: &Solution) -> anyhow::Result<bool> {
+        let conn = self.solution_cache_db.lock().await;
+
+        let row: Option<u32> = conn
+            .prepare_cached(
+                "SELECT 1 FROM Solution WHERE iid = ?1 AND digest = ?2 AND uploaded LIMIT 1",
+            )?
+            .query_row(
+                (iid.iid_to_u32(), solution.compute_digest().as_slice()),
+                |row| row.get(0),
+            )
+            .optional()
+            .with_context(|| format!("Looking up solution for {iid:?} in cache"))?;
+
+        Ok(row.is_some())
+    }
+
+    /// Returns the smallest solution we know for the instance (the oldest one in case of ties)
+    pub async fn best_solution(&self, iid: IId) -> anyhow::Result<Option<CachedSolution>> {
+        let conn = self.solution_cache_db.lock().await;
+
+        let row: Option<(Option<String>, String, u32, Vec<u8>)> = conn
+            .prepare_cached(
+                "SELECT solver_uuid, run_uuid, score, data FROM Solution WHERE iid = ?1 ORDER BY score ASC, created_at ASC LIMIT 1",
+            )?
+            .query_row([iid.iid_to_u32()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .optional()
+            .with_context(|| format!("Fetching best solution for {iid:?} from cache"))?;
+
+        let Some((solver_uuid, run_uuid, score, data)) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(CachedSolution {
+            iid,
+            solver_uuid: solver_uuid.map(|u| Uuid::parse_str(&u)).transpose()?,
+            run_uuid: Uuid::parse_str(&run_uuid)?,
+            score,
+            data: String::from_utf8(data)?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    const PREFIX: &str = "stride-solution-cache-db-test";
+    const IID: IId = IId::new(42);
+
+    #[tokio::test]
+    async fn create_and_connect() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let db_path = tmp_dir.path().join("cache.db");
+        let solution = Solution::from_0indexed_vec(vec![0, 2]);
+
+        // the first call will create the db
+        {
+            let db = SolutionCacheDB::new(db_path.as_path()).await.unwrap();
+            db.insert(IID, None, Uuid::new_v4(), &solution, true)
+                .await
+                .unwrap();
+        }
+
+        // the second should reconnect to the existing db
+        {
+            let db = SolutionCacheDB::new(db_path.as_path()).await.unwrap();
+            assert!(db.was_uploaded(IID, &solution).await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn was_uploaded() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let db = SolutionCacheDB::new(tmp_dir.path().join("cache.db").as_path())
+            .await
+            .unwrap();
+
+        let uploaded = Solution::from_0indexed_vec(vec![0, 2]);
+        let not_uploaded = Solution::from_0indexed_vec(vec![1, 3]);
+
+        db.insert(IID, None, Uuid::new_v4(), &uploaded, true)
+            .await
+            .unwrap();
+        db.insert(IID, None, Uuid::new_v4(), &not_uploaded, false)
+            .await
+            .unwrap();
+
+        assert!(db.was_uploaded(IID, &uploaded).await.unwrap());
+        assert!(!db.was_uploaded(IID, &not_uploaded).await.unwrap());
+        assert!(!db
+            .was_uploaded(IId::new(IID.iid_to_u32() + 1), &uploaded)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn insert_duplicate() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let db = SolutionCacheDB::new(tmp_dir.path().join("cache.db").as_path())
+            .await
+            .unwrap();
+
+        let solution = Solution::from_0indexed_vec(vec![0, 2]);
+        for uploaded in [false, true, false] {
+            db.insert(IID, None, Uuid::new_v4(), &solution, uploaded)
+                .await
+                .unwrap();
+        }
+
+        // a later insert never clears the flag
+        assert!(db.was_uploaded(IID, &solution).await.unwrap());
+
+        let conn = db.solution_cache_db.lock().await;
+        let rows: u32 = conn
+            .query_row("SELECT COUNT(*) FROM Solution", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[tokio::test]
+    async fn mark_uploaded() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let db = SolutionCacheDB::new(tmp_dir.path().join("cache.db").as_path())
+            .await
+            .unwrap();
+
+        let solution = Solution::from_0indexed_vec(vec![0, 2]);
+        db.insert(IID, None, Uuid::new_v4(), &solution, false)
+            .await
+            .unwrap();
+        assert!(!db.was_uploaded(IID, &solution).await.unwrap());
+
+        db.mark_uploaded(IID, solution.compute_digest().as_slice())
+            .await
+            .unwrap();
+        assert!(db.was_uploaded(IID, &solution).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn best_solution() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let db = SolutionCacheDB::new(tmp_dir.path().join("cache.db").as_path())
+            .await
+            .unwrap();
+
+        assert!(db.best_solution(IID).await.unwrap().is_none());
+
+        let solver_uuid = Uuid::new_v4();
+        let run_uuid = Uuid::new_v4();
+        db.insert(
+            IID,
+            None,
+            Uuid::new_v4(),
+            &Solution::from_0indexed_vec(vec![0, 1, 2]),
+            false,
+        )
+        .await
+        .unwrap();
+        db.insert(
+            IID,
+            Some(solver_uuid),
+            run_uuid,
+            &Solution::from_0indexed_vec(vec![0, 2]),
+            true,
+        )
+        .await
+        .unwrap();
+
+        let best = db.best_solution(IID).await.unwrap().unwrap();
+        assert_eq!(
+            best,
+            CachedSolution {
+                iid: IID,
+                solver_uuid: Some(solver_uuid),
+                run_uuid,
+                score: 2,
+                data: "2\n1\n3\n".into(),
+            }
+        );
+    }
+}
@@ -15,6 +15,18 @@ pub struct SolutionUploadRequest<'a> {
     #[builder(setter(into, strip_option), default)]
     seconds_computed: Option<f64>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option), default)]
+    peak_memory_kb: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option), default)]
+    user_seconds_computed: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option), default)]
+    system_seconds_computed: Option<f64>,
+
     result: &'a SolverResult,
 
     #[serde(skip_serializing_if = "is_false")]
@@ -35,29 +47,79 @@ pub fn is_score_good_enough_for_upload(solution_score: u32, best_score: Option<u
     }
 }
 
+/// Outcome of a single attempt to upload a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadStatus {
+    Accepted,
+    /// The server refused the request; sending it again will not help
+    Rejected,
+    /// The server could not be reached (or failed internally); the request may succeed later
+    Failed,
+}
+
 impl SolutionUploadRequest<'_> {
+    pub fn instance_id(&self) -> IId {
+        self.instance_id
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
     pub async fn upload(&self, server_conn: &ServerConnection) -> anyhow::Result<()> {
-        let url = server_conn.base_url().join("api/solutions/new").unwrap();
-
-        let resp = server_conn
-            .client_arc()
-            .post(url)
-            .json(self)
-            .send()
-            .await
-            .expect("Failed to upload solution");
-
-        if !resp.status().is_success() {
-            debug!(
-                "Failed to upload solution for {:?}; response: {:?}",
-                self.instance_id,
-                resp.text().await
-            );
-            trace!("Request was: {:?}", self);
-            anyhow::bail!("Failed to upload solution");
+        match send_upload_request(server_conn, self.instance_id, &self.to_json()?).await {
+            UploadStatus::Accepted => Ok(()),
+            UploadStatus::Rejected => anyhow::bail!("Server rejected solution"),
+            UploadStatus::Failed => anyhow::bail!("Failed to upload solution"),
+        }
+    }
+}
+
+/// Sends a request previously serialized with [`SolutionUploadRequest::to_json`]
+pub async fn send_upload_request(
+    server_conn: &ServerConnection,
+    instance_id: IId,
+    json: &str,
+) -> UploadStatus {
+    if server_conn.is_offline() {
+        debug!("Offline mode; not uploading solution for {instance_id:?}");
+        return UploadStatus::Failed;
+    }
+
+    let url = server_conn.base_url().join("api/solutions/new").unwrap();
+
+    let resp = match server_conn
+        .client_arc()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(json.to_string())
+        .send()
+        .await
+    {
+        Ok(resp) => resp,
+        Err(e) => {
+            debug!("Failed to upload solution for {instance_id:?}: {e}");
+            return UploadStatus::Failed;
         }
+    };
+
+    let status = resp.status();
+    if status.is_success() {
+        return UploadStatus::Accepted;
+    }
+
+    debug!(
+        "Failed to upload solution for {:?}; response: {:?}",
+        instance_id,
+        resp.text().await
+    );
+    trace!("Request was: {json}");
 
-        Ok(())
+    if status.is_client_error() {
+        UploadStatus::Rejected
+    } else {
+        UploadStatus::Failed
     }
 }
 
@@ -156,4 +218,24 @@ mod test {
 
         request.upload(&conn).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn upload_to_unreachable_server() {
+        let solution = SolverResult::Valid {
+            data: SOLUTION.into(),
+        };
+        let conn = ServerConnection::new("http://127.0.0.1:1".parse().unwrap()).unwrap();
+
+        let request = SolutionUploadRequestBuilder::default()
+            .instance_id(IID)
+            .run_uuid(uuid::Uuid::new_v4())
+            .solver_uuid(None)
+            .result(&solution)
+            .build()
+            .unwrap();
+
+        let status = send_upload_request(&conn, IID, &request.to_json().unwrap()).await;
+        assert_eq!(status, UploadStatus::Failed);
+        assert!(request.upload(&conn).await.is_err());
+    }
 }
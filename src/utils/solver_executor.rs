@@ -1,28 +1,61 @@
-use std::{fs::File, path::PathBuf, process::ExitStatus, time::Duration};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    os::unix::process::{CommandExt, ExitStatusExt},
+    path::PathBuf,
+    process::{Child, Command, ExitStatus},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::Context;
 use derive_builder::Builder;
 use serde::Serialize;
 use std::io::BufReader;
 use tokio::{
-    process::{Child, Command},
+    sync::watch,
     time::{timeout, Instant},
 };
 use tracing::{debug, trace};
 
-use crate::pace::{graph::Node, instance_reader::PaceReader, Solution};
+use crate::pace::{
+    graph::{CsrGraph, Node},
+    instance_reader::PaceReader,
+    Solution,
+};
 
-use super::IId;
+use super::{intermediate_tracker::IntermediateTracker, IId};
 
 #[derive(Debug, Serialize, Eq, PartialEq)]
 #[serde(tag = "status", rename_all = "lowercase")]
 pub enum SolverResult {
-    Valid { data: Vec<Node> },
+    Valid {
+        data: Vec<Node>,
+    },
     ValidCached,
     Infeasible,
-    SyntaxError, // TODO: distinguish between syntax and runner errors
+    // the details of failures are only reported locally; the server merely receives the status
+    SyntaxError {
+        #[serde(skip)]
+        message: String,
+    },
     Timeout,
     IncompleteOutput,
+    Interrupted,
+    // the server does not distinguish the following cases; it used to see them as syntax errors
+    #[serde(rename = "syntaxerror")]
+    MemoryLimitExceeded,
+    #[serde(rename = "syntaxerror")]
+    Crashed {
+        #[serde(skip)]
+        signal: i32,
+    },
+    #[serde(rename = "syntaxerror")]
+    NonZeroExitCode {
+        #[serde(skip)]
+        code: i32,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -30,6 +63,35 @@ enum ChildExitCode {
     BeforeTimeout(ExitStatus),
     WithinGrace(ExitStatus),
     Timeout,
+    Interrupted,
+}
+
+/// Resources consumed by the solver (and its reaped children) as reported by the kernel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub peak_rss_kb: u64,
+    pub user_time: Duration,
+    pub system_time: Duration,
+}
+
+impl ResourceUsage {
+    fn from_rusage(usage: &libc::rusage) -> Self {
+        // Linux reports the maximum resident set size in kilobytes, OSX in bytes
+        #[cfg(target_os = "macos")]
+        let peak_rss_kb = usage.ru_maxrss as u64 / 1024;
+        #[cfg(not(target_os = "macos"))]
+        let peak_rss_kb = usage.ru_maxrss as u64;
+
+        let to_duration = |tv: libc::timeval| {
+            Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+        };
+
+        Self {
+            peak_rss_kb,
+            user_time: to_duration(usage.ru_utime),
+            system_time: to_duration(usage.ru_stime),
+        }
+    }
 }
 
 impl SolverResult {
@@ -51,27 +113,203 @@ pub struct SolverExecutor {
     timeout: Duration,
     grace: Duration,
 
+    /// Upper bound on the address space of the solver (RLIMIT_AS)
+    #[builder(default)]
+    memory_limit_mb: Option<u64>,
+
+    /// Upper bound on the CPU time of the solver (RLIMIT_CPU); the hard limit includes the grace period
+    #[builder(default)]
+    cpu_time_limit: Option<Duration>,
+
+    /// Once `true` is sent, the solver is stopped as if the timeout had been reached
+    #[builder(default, setter(strip_option))]
+    interrupt: Option<watch::Receiver<bool>>,
+
+    /// The process group of the solver is registered here while it is running
+    #[builder(default)]
+    process_groups: ProcessGroups,
+
+    /// Lets the solver append intermediate solutions to the file given in `STRIDE_INTERMEDIATE_PATH`;
+    /// they are verified and recorded in a score-over-time trace
+    #[builder(default)]
+    track_intermediate: bool,
+
+    /// Requires `track_intermediate`; a solver that misses the deadline is scored by its best
+    /// intermediate solution
+    #[builder(default)]
+    anytime: bool,
+
+    /// Requires `track_intermediate`; once a feasible intermediate solution of at most this
+    /// score is observed, the solver is stopped early (SIGTERM, then SIGKILL after the grace period)
+    #[builder(default)]
+    target_score: Option<u32>,
+
+    /// Time since the start at which the intermediate solution the result is scored by was read
+    #[builder(setter(skip))]
+    scored_from_intermediate: Option<Duration>,
+
+    /// Time since the start at which an intermediate solution reached `target_score`
+    #[builder(setter(skip))]
+    target_reached: Option<Duration>,
+
     #[builder(setter(skip))]
     runtime: Option<Duration>,
 
+    #[builder(setter(skip))]
+    resource_usage: Option<ResourceUsage>,
+
     instance_id: IId,
     instance_data: String,
 }
 
+/// Process groups of running solvers; shared between executors, so that all of them can be
+/// killed at once if the runner has to exit immediately
+#[derive(Debug, Clone, Default)]
+pub struct ProcessGroups(Arc<Mutex<HashSet<u32>>>);
+
+impl ProcessGroups {
+    /// Sends SIGKILL to all registered process groups
+    pub fn kill_all(&self) {
+        for &pgid in self.0.lock().unwrap().iter() {
+            signal_process_group(pgid, libc::SIGKILL);
+        }
+    }
+
+    fn insert(&self, pgid: u32) {
+        self.0.lock().unwrap().insert(pgid);
+    }
+
+    fn remove(&self, pgid: u32) {
+        self.0.lock().unwrap().remove(&pgid);
+    }
+}
+
 const PATH_STDIN: &str = "stdin.gr";
 const PATH_STDOUT: &str = "stdout";
 const PATH_STDERR: &str = "stderr";
+const PATH_VERIFICATION: &str = "verification";
+const PATH_INTERMEDIATE: &str = "intermediate";
+const PATH_TRACE: &str = "trace.csv";
+
+/// How often the file of intermediate solutions is checked for new data
+const INTERMEDIATE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Only the end of stderr is scanned for signs of a failed allocation
+const STDERR_TAIL_BYTES: u64 = 16 * 1024;
+
+/// Messages printed by common runtimes (Rust, C++, Python, Java, libc) if an allocation fails
+const OUT_OF_MEMORY_MARKERS: [&str; 6] = [
+    "memory allocation of",
+    "bad_alloc",
+    "MemoryError",
+    "OutOfMemoryError",
+    "out of memory",
+    "Cannot allocate memory",
+];
+
+/// Minimum fraction of the memory limit the solver must have used for an allocation failure to
+/// count as hitting the limit. A buffer that doubles its capacity fails to grow while only about
+/// a third of the limit is in use, and the address space also covers non-resident mappings.
+const MEMORY_LIMIT_MIN_USAGE: f64 = 0.25;
+
+/// A solver aborting or crashing without an allocation error message (e.g., since it does not
+/// check the result of `malloc`) is only blamed on the memory limit above this fraction of it
+const MEMORY_LIMIT_SILENT_USAGE: f64 = 0.75;
 
 impl SolverExecutor {
     pub async fn run(&mut self) -> anyhow::Result<SolverResult> {
         self.move_instance_data_to_file()?;
 
+        // intermediate solutions are verified while the solver is running
+        let graph = if self.track_intermediate {
+            Some(self.read_graph()?)
+        } else {
+            None
+        };
+
         // spawn and execute solver as child
         let start_time = Instant::now();
+        let mut tracker = graph
+            .as_ref()
+            .map(|_| IntermediateTracker::new(self.filename(PATH_INTERMEDIATE), start_time));
         let child = self.spawn_child()?;
-        let wait_result = self.timeout_wait_for_child_to_complete(child).await?;
+        let early_stop = self.target_score.map(|target| EarlyStop {
+            target,
+            pgid: child.id(),
+            grace: self.grace,
+            reached: None,
+            killed: false,
+        });
+        let (wait_result, early_stop) = match (tracker.as_mut(), graph.as_ref()) {
+            (Some(tracker), Some(graph)) => {
+                let mut early_stop = early_stop;
+                let wait = self.timeout_wait_for_child_to_complete(child);
+                let wait_result =
+                    track_intermediate_while(tracker, graph, early_stop.as_mut(), wait).await?;
+                (wait_result, early_stop)
+            }
+            _ => (self.timeout_wait_for_child_to_complete(child).await?, None),
+        };
         self.runtime = Some(start_time.elapsed());
+        self.target_reached = early_stop.and_then(|e| e.reached);
+
+        let best_intermediate = match (tracker, graph.as_ref()) {
+            (Some(mut tracker), Some(graph)) => {
+                // pick up solutions written right before the solver terminated
+                if let Err(e) = tracker.poll(graph) {
+                    debug!("{:?} Reading intermediate solutions: {e}", self.instance_id);
+                }
+                tracker.write_trace(std::io::BufWriter::new(File::create(
+                    self.filename(PATH_TRACE),
+                )?))?;
+                tracker.take_best()
+            }
+            _ => None,
+        };
+
+        let result = self.evaluate(wait_result, graph)?;
+
+        // a solver that missed the deadline is scored by its best intermediate solution
+        if self.anytime && result == SolverResult::Timeout {
+            if let Some((elapsed, data)) = best_intermediate {
+                self.scored_from_intermediate = Some(elapsed);
+                return Ok(SolverResult::Valid { data });
+            }
+        }
+
+        // a solver stopped early may not produce a (better) final solution, e.g., if it
+        // does not handle SIGTERM or exits with an error code upon it (as shell wrappers do);
+        // an infeasible or malformed final solution is still reported
+        if self.target_reached.is_some() {
+            if let Some((elapsed, data)) = best_intermediate {
+                let replace = match result.score() {
+                    Some(score) => data.len() < score as usize,
+                    None => matches!(
+                        result,
+                        SolverResult::Timeout
+                            | SolverResult::NonZeroExitCode { .. }
+                            | SolverResult::Crashed {
+                                signal: libc::SIGTERM | libc::SIGKILL
+                            }
+                    ),
+                };
+
+                if replace {
+                    self.scored_from_intermediate = Some(elapsed);
+                    return Ok(SolverResult::Valid { data });
+                }
+            }
+        }
+
+        Ok(result)
+    }
 
+    /// Classifies the solver's termination and verifies its final solution
+    fn evaluate(
+        &self,
+        wait_result: ChildExitCode,
+        graph: Option<CsrGraph>,
+    ) -> anyhow::Result<SolverResult> {
         let status = match wait_result {
             ChildExitCode::BeforeTimeout(status) => status,
             ChildExitCode::WithinGrace(status) => {
@@ -82,14 +320,30 @@ impl SolverExecutor {
                 }
             }
             ChildExitCode::Timeout => return Ok(SolverResult::Timeout),
+            ChildExitCode::Interrupted => return Ok(SolverResult::Interrupted),
         };
 
-        // TODO: we might want to handle a non-zero exit status differently
         if !status.success() {
-            return Ok(SolverResult::SyntaxError);
+            if self.cpu_time_limit_exceeded(status) {
+                return Ok(SolverResult::Timeout);
+            }
+
+            if self.memory_limit_exceeded(status)? {
+                return Ok(SolverResult::MemoryLimitExceeded);
+            }
+
+            return Ok(match (status.signal(), status.code()) {
+                (Some(signal), _) => SolverResult::Crashed { signal },
+                (None, Some(code)) => SolverResult::NonZeroExitCode { code },
+                (None, None) => unreachable!("a process either exits or is terminated by a signal"),
+            });
         }
 
-        self.verify_solution()
+        let graph = match graph {
+            Some(graph) => graph,
+            None => self.read_graph()?,
+        };
+        self.verify_solution(&graph)
     }
 
     pub fn delete_files(&self) -> anyhow::Result<()> {
@@ -104,6 +358,12 @@ impl SolverExecutor {
         std::fs::remove_file(stdout)?;
         std::fs::remove_file(stderr)?;
 
+        // the trace is kept, since it summarizes the intermediate solutions
+        let intermediate = self.filename(PATH_INTERMEDIATE);
+        if intermediate.exists() {
+            std::fs::remove_file(intermediate)?;
+        }
+
         Ok(())
     }
 
@@ -111,34 +371,112 @@ impl SolverExecutor {
         self.runtime
     }
 
-    fn verify_solution(&self) -> anyhow::Result<SolverResult> {
+    pub fn resource_usage(&self) -> Option<ResourceUsage> {
+        self.resource_usage
+    }
+
+    /// The result is the solver's best intermediate solution, since it timed out or was stopped early
+    pub fn scored_from_intermediate(&self) -> bool {
+        self.scored_from_intermediate.is_some()
+    }
+
+    /// Time since the start at which the intermediate solution the result is scored by was read
+    pub fn intermediate_elapsed(&self) -> Option<Duration> {
+        self.scored_from_intermediate
+    }
+
+    /// Time since the start at which an intermediate solution reached the target score;
+    /// the solver was stopped early at that point
+    pub fn target_reached(&self) -> Option<Duration> {
+        self.target_reached
+    }
+
+    fn read_graph(&self) -> anyhow::Result<CsrGraph> {
         let instance_file = BufReader::new(File::open(self.filename(PATH_STDIN))?);
-        let instance_reader = PaceReader::try_new(instance_file)?;
-        let n = instance_reader.number_of_nodes();
-        let mut edges = Vec::with_capacity(instance_reader.number_of_edges() as usize);
-        for edge in instance_reader {
-            edges.push(edge?);
-        }
+        Ok(CsrGraph::try_from_reader(PaceReader::try_new(
+            instance_file,
+        )?)?)
+    }
 
+    fn verify_solution(&self, graph: &CsrGraph) -> anyhow::Result<SolverResult> {
+        // out-of-range nodes are reported by the verifier
         let solution_file = BufReader::new(File::open(self.filename(PATH_STDOUT))?);
-        let solution = match Solution::read(solution_file, Some(n)) {
+        let solution = match Solution::read(solution_file, None) {
             Ok(s) => s,
             Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
                 return Ok(SolverResult::IncompleteOutput);
             }
             Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
-                return Ok(SolverResult::SyntaxError);
+                return Ok(SolverResult::SyntaxError {
+                    message: e.to_string(),
+                });
             }
             Err(e) => return Err(e.into()),
         };
 
-        match solution.valid_domset_for_instance(n, edges.into_iter()) {
-            Ok(true) => anyhow::Ok(SolverResult::Valid {
-                data: solution.take_1indexed_solution(),
-            }),
-            Ok(false) => anyhow::Ok(SolverResult::Infeasible),
-            Err(e) => Err(e.into()),
+        let report = solution.verify(graph);
+        if !report.is_feasible() {
+            debug!(
+                "Infeasible solution for {:?}: {}",
+                self.instance_id,
+                report.summary()
+            );
+            report.write(File::create(self.filename(PATH_VERIFICATION))?)?;
+            return Ok(SolverResult::Infeasible);
         }
+
+        Ok(SolverResult::Valid {
+            data: solution.take_1indexed_solution(),
+        })
+    }
+
+    /// The kernel sends SIGXCPU once the soft limit is reached and SIGKILL at the hard limit.
+    /// Since a SIGKILL may also originate from elsewhere (e.g., the OOM killer), the solver
+    /// must have actually consumed the CPU time.
+    fn cpu_time_limit_exceeded(&self, status: ExitStatus) -> bool {
+        let (Some(limit), Some(usage)) = (self.cpu_time_limit, self.resource_usage) else {
+            return false;
+        };
+
+        // the limit is applied with a granularity of seconds; see `apply_resource_limits`
+        let limit = Duration::from_secs(limit.as_secs().max(1));
+
+        matches!(status.signal(), Some(libc::SIGXCPU) | Some(libc::SIGKILL))
+            && usage.user_time + usage.system_time >= limit
+    }
+
+    /// A process hitting RLIMIT_AS is not signalled by the kernel; instead its allocations fail.
+    /// We hence look for the error messages typically produced in this situation. A solver that
+    /// merely logs such a message but terminates successfully is not affected, and neither is one
+    /// that crashes far below the limit (see [`MEMORY_LIMIT_MIN_USAGE`]). Without such a message,
+    /// only an abort or segfault close to the limit counts (see [`MEMORY_LIMIT_SILENT_USAGE`]).
+    fn memory_limit_exceeded(&self, status: ExitStatus) -> anyhow::Result<bool> {
+        let (Some(limit_mb), Some(usage)) = (self.memory_limit_mb, self.resource_usage) else {
+            return Ok(false);
+        };
+
+        let usage = usage.peak_rss_kb as f64 / (limit_mb * 1024) as f64;
+        if status.success() || usage < MEMORY_LIMIT_MIN_USAGE {
+            return Ok(false);
+        }
+
+        if usage >= MEMORY_LIMIT_SILENT_USAGE
+            && matches!(status.signal(), Some(libc::SIGABRT | libc::SIGSEGV))
+        {
+            return Ok(true);
+        }
+
+        let mut file = File::open(self.filename(PATH_STDERR))?;
+        let len = file.metadata()?.len();
+        file.seek(SeekFrom::Start(len.saturating_sub(STDERR_TAIL_BYTES)))?;
+
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+        let tail = String::from_utf8_lossy(&tail);
+
+        Ok(OUT_OF_MEMORY_MARKERS
+            .iter()
+            .any(|marker| tail.contains(marker)))
     }
 
     fn filename(&self, suffix: &str) -> PathBuf {
@@ -153,69 +491,302 @@ impl SolverExecutor {
         Ok(())
     }
 
+    /// The child is spawned with `std::process` rather than `tokio::process`, since we reap it
+    /// ourselves using `wait4` to obtain its resource usage (see [`wait_for_process`]).
     fn spawn_child(&mut self) -> Result<Child, anyhow::Error> {
         let stdin = File::open(self.filename(PATH_STDIN)).with_context(|| "Open STDIN")?;
         let stdout = File::create(self.filename(PATH_STDOUT)).with_context(|| "Open STDOUT")?;
         let stderr = File::create(self.filename(PATH_STDERR)).with_context(|| "Open STDERR")?;
 
+        if self.track_intermediate {
+            // remove solutions of a previous execution on the same instance
+            let path = std::path::absolute(self.filename(PATH_INTERMEDIATE))?;
+            let _ = std::fs::remove_file(&path);
+            self.env.push((
+                "STRIDE_INTERMEDIATE_PATH".into(),
+                path.display().to_string(),
+            ));
+        }
+
         trace!(
             "Spawn solver {:?} with args {:?}",
             self.solver_path,
             &self.args
         );
-        let child = Command::new(&self.solver_path)
+        let mut command = Command::new(&self.solver_path);
+        command
             .args(&self.args)
             .envs(self.env.iter().cloned())
             .stdin(stdin)
             .stdout(stdout)
             .stderr(stderr)
-            .spawn()
-            .with_context(|| "Spawn solver as child")?;
+            .process_group(0); // own group, so that we can signal all processes spawned by the solver
+        self.apply_resource_limits(&mut command);
+
+        let child = command.spawn().with_context(|| "Spawn solver as child")?;
         Ok(child)
     }
 
+    fn apply_resource_limits(&self, command: &mut Command) {
+        let memory_limit = self
+            .memory_limit_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024));
+        let cpu_limit = self.cpu_time_limit.map(|soft| {
+            let soft = soft.as_secs().max(1);
+            (soft, soft + self.grace.as_secs().max(1))
+        });
+
+        if memory_limit.is_none() && cpu_limit.is_none() {
+            return;
+        }
+
+        // the closure is executed in the forked child right before exec; it may only use
+        // async-signal-safe functions, which setrlimit is.
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(bytes) = memory_limit {
+                    let limit = libc::rlimit {
+                        rlim_cur: bytes as libc::rlim_t,
+                        rlim_max: bytes as libc::rlim_t,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+
+                if let Some((soft, hard)) = cpu_limit {
+                    let limit = libc::rlimit {
+                        rlim_cur: soft as libc::rlim_t,
+                        rlim_max: hard as libc::rlim_t,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_CPU, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+
+                Ok(())
+            });
+        }
+    }
+
     /// In case of no error, we return
     ///  - Some(ExitStatus) if the child has exited
     ///  - None if the child has been killed using SIGKILL
+    ///
+    /// The solver runs in its own process group (see [`SolverExecutor::spawn_child`]). All signals
+    /// are sent to the whole group, so that processes forked by the solver (e.g., if it is started
+    /// by a wrapper script) are stopped as well. Once the solver exited, all remaining members of
+    /// the group are killed.
+    ///
+    /// Once the child has been reaped, its resource usage is available via [`SolverExecutor::resource_usage`].
     async fn timeout_wait_for_child_to_complete(
-        &self,
-        mut child: Child,
+        &mut self,
+        child: Child,
     ) -> anyhow::Result<ChildExitCode> {
-        // we get an error if we run into the timeout
-        if let Ok(res) = timeout(self.timeout, child.wait()).await {
-            return Ok(ChildExitCode::BeforeTimeout(res?));
-        }
+        // the pgid matches the pid of the child
+        let pid = child.id();
+        let process_groups = self.process_groups.clone();
+        process_groups.insert(pid);
+        let mut wait = tokio::task::spawn_blocking(move || wait_for_process(pid, &process_groups));
 
-        debug!(
-            "{:?} Timeout after {}s reached; send sigterm child",
-            self.instance_id,
-            self.timeout.as_secs()
-        );
+        let (exit_code, usage) = self.timeout_wait_for_process_group(&mut wait, pid).await?;
+        self.resource_usage = Some(usage);
+        Ok(exit_code)
+    }
 
-        // send SIGTERM to the child (we use unsafe here, because I do not want to pull a crate for this one line)
-        if let Some(pid) = child.id() {
-            // we only get None if the child has already exited
-            unsafe {
-                libc::kill(pid as i32, libc::SIGTERM);
+    async fn timeout_wait_for_process_group(
+        &self,
+        wait: &mut WaitHandle,
+        pgid: u32,
+    ) -> anyhow::Result<(ChildExitCode, ResourceUsage)> {
+        let interrupted = tokio::select! {
+            // we get an error if we run into the timeout
+            res = timeout(self.timeout, &mut *wait) => {
+                if let Ok(res) = res {
+                    let (status, usage) = res??;
+                    return Ok((ChildExitCode::BeforeTimeout(status), usage));
+                }
+
+                debug!(
+                    "{:?} Timeout after {}s reached; send sigterm to process group",
+                    self.instance_id,
+                    self.timeout.as_secs()
+                );
+                false
             }
-        }
+            _ = self.wait_for_interrupt() => {
+                debug!("{:?} Interrupted; send sigterm to process group", self.instance_id);
+                true
+            }
+        };
+
+        signal_process_group(pgid, libc::SIGTERM);
 
         // issue a grace period
         if !self.grace.is_zero() {
-            if let Ok(res) = timeout(self.grace, child.wait()).await {
-                return Ok(ChildExitCode::WithinGrace(res?));
+            if let Ok(res) = timeout(self.grace, &mut *wait).await {
+                let (status, usage) = res??;
+                if interrupted {
+                    return Ok((ChildExitCode::Interrupted, usage));
+                }
+                return Ok((ChildExitCode::WithinGrace(status), usage));
             }
         }
 
         debug!(
-            "{:?} Grace period after {}s reached; kill child",
+            "{:?} Grace period after {}s reached; kill process group",
             self.instance_id,
             self.timeout.as_secs()
         );
 
-        child.kill().await?;
+        signal_process_group(pgid, libc::SIGKILL);
+        let (_, usage) = wait.await??;
+
+        if interrupted {
+            return Ok((ChildExitCode::Interrupted, usage));
+        }
+        Ok((ChildExitCode::Timeout, usage))
+    }
+
+    /// Completes once an interrupt is requested; never completes if there is no interrupt channel
+    async fn wait_for_interrupt(&self) {
+        if let Some(mut interrupt) = self.interrupt.clone() {
+            if interrupt.wait_for(|x| *x).await.is_ok() {
+                return;
+            }
+        }
 
-        Ok(ChildExitCode::Timeout)
+        std::future::pending().await
+    }
+}
+
+/// Stops the solver once an intermediate solution reaches the target score
+struct EarlyStop {
+    target: u32,
+    pgid: u32,
+    grace: Duration,
+    reached: Option<Duration>,
+    killed: bool,
+}
+
+impl EarlyStop {
+    /// Sends SIGTERM once the target is reached and SIGKILL after the grace period
+    fn check(&mut self, tracker: &IntermediateTracker) {
+        match self.reached {
+            None => {
+                let Some(reached) = tracker
+                    .trace()
+                    .iter()
+                    .find(|s| s.feasible && s.score <= self.target)
+                else {
+                    return;
+                };
+
+                debug!(
+                    "Target score {} reached after {:?}; send sigterm to process group",
+                    self.target, reached.elapsed
+                );
+                self.reached = Some(reached.elapsed);
+                signal_process_group(self.pgid, libc::SIGTERM);
+            }
+            Some(reached) => {
+                if !self.killed && tracker.elapsed() > reached + self.grace {
+                    debug!("Grace period after reaching the target score; kill process group");
+                    signal_process_group(self.pgid, libc::SIGKILL);
+                    self.killed = true;
+                }
+            }
+        }
+    }
+}
+
+/// Polls the file of intermediate solutions until `wait` completes
+async fn track_intermediate_while<F: std::future::Future>(
+    tracker: &mut IntermediateTracker,
+    graph: &CsrGraph,
+    mut early_stop: Option<&mut EarlyStop>,
+    wait: F,
+) -> F::Output {
+    tokio::pin!(wait);
+    let mut interval = tokio::time::interval(INTERMEDIATE_POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            result = &mut wait => return result,
+            _ = interval.tick() => {
+                if let Err(e) = tracker.poll(graph) {
+                    debug!("Reading intermediate solutions: {e}");
+                }
+                if let Some(early_stop) = early_stop.as_deref_mut() {
+                    early_stop.check(tracker);
+                }
+            }
+        }
+    }
+}
+
+type WaitHandle = tokio::task::JoinHandle<std::io::Result<(ExitStatus, ResourceUsage)>>;
+
+/// Blocks until the process exits and reaps it; hence, it has to be executed in a blocking task.
+///
+/// Right before reaping the process, all remaining members of its process group are killed.
+/// As long as the exited leader is not reaped, its pid (and hence the pgid) cannot be reused;
+/// so the signal cannot hit an unrelated process group. For the same reason, the group is
+/// unregistered from `process_groups` before reaping.
+fn wait_for_process(
+    pid: u32,
+    process_groups: &ProcessGroups,
+) -> std::io::Result<(ExitStatus, ResourceUsage)> {
+    // wait for the process to exit, but keep it as a zombie
+    loop {
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let res = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if res == 0 {
+            break;
+        }
+
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+
+    signal_process_group(pid, libc::SIGKILL);
+    process_groups.remove(pid);
+
+    let mut status: libc::c_int = 0;
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+
+    loop {
+        let res = unsafe { libc::wait4(pid as libc::pid_t, &mut status, 0, &mut usage) };
+        if res >= 0 {
+            break;
+        }
+
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+
+    Ok((
+        ExitStatus::from_raw(status),
+        ResourceUsage::from_rusage(&usage),
+    ))
+}
+
+/// Sends a signal to all processes in the group
+fn signal_process_group(pgid: u32, signal: libc::c_int) {
+    // we use unsafe here, because I do not want to pull a crate for this one line
+    unsafe {
+        libc::killpg(pgid as libc::pid_t, signal);
     }
 }
 
@@ -275,6 +846,25 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_serialize_failures_without_details() {
+        for result in [
+            SolverResult::SyntaxError {
+                message: "line 3: unexpected token".into(),
+            },
+            SolverResult::MemoryLimitExceeded,
+            SolverResult::Crashed {
+                signal: libc::SIGSEGV,
+            },
+            SolverResult::NonZeroExitCode { code: 3 },
+        ] {
+            assert_eq!(
+                serde_json::to_string(&result).unwrap(),
+                r#"{"status":"syntaxerror"}"#
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_normal_exit() {
         #[allow(unused)]
@@ -350,6 +940,50 @@ mod test {
         assert_eq!(status, ChildExitCode::Timeout);
     }
 
+    fn process_is_alive(pid: libc::pid_t) -> bool {
+        if unsafe { libc::kill(pid, 0) } != 0 {
+            return false;
+        }
+
+        // the process may be a zombie waiting to be reaped by its new parent
+        match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+            Ok(stat) => !stat.contains(") Z "),
+            Err(_) => true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kill_process_group() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) =
+            default_test_executor(BIN_DUMMY, vec!["fork-never-terminate".into()]);
+        exec.move_instance_data_to_file().unwrap();
+
+        let child = exec.spawn_child().unwrap();
+        let status = exec
+            .timeout_wait_for_child_to_complete(child)
+            .await
+            .unwrap();
+        assert_eq!(status, ChildExitCode::Timeout);
+
+        let grandchild: libc::pid_t = std::fs::read_to_string(exec.filename(PATH_STDOUT))
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+
+        // give the kernel some time to deliver the signal
+        let start = std::time::Instant::now();
+        while process_is_alive(grandchild) && start.elapsed().as_millis() < 1000 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert!(
+            !process_is_alive(grandchild),
+            "Process {grandchild} spawned by solver is still running"
+        );
+    }
+
     #[tokio::test]
     async fn test_run_greedy_ok() {
         for args in [vec![], vec!["-c"], vec!["-c", "-e"], vec!["-c", "-e", "-t"]] {
@@ -377,6 +1011,9 @@ mod test {
             crate::utils::solver_executor::SolverResult::Infeasible => {}
             _ => panic!("Unexpected result: {:?}", status),
         }
+
+        let report = std::fs::read_to_string(exec.filename(PATH_VERIFICATION)).unwrap();
+        assert!(report.starts_with("feasible: no\n"), "{report}");
     }
 
     #[tokio::test]
@@ -390,4 +1027,353 @@ mod test {
             _ => panic!("Unexpected result: {:?}", status),
         }
     }
+
+    #[tokio::test]
+    async fn test_run_greedy_timeout_with_intermediate() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_GREEDY, vec!["-n".into(), "-a".into()]);
+        exec.track_intermediate = true;
+        exec.anytime = true;
+        let status = exec.run().await.unwrap();
+
+        // REF_DATA has 9 nodes; the greedy solution is smaller
+        match status {
+            SolverResult::Valid { data } => assert!(data.len() < 9, "{data:?}"),
+            _ => panic!("Unexpected result: {:?}", status),
+        }
+        assert!(exec.scored_from_intermediate());
+        assert!(exec.intermediate_elapsed().unwrap() < exec.runtime().unwrap());
+
+        let trace = std::fs::read_to_string(exec.filename(PATH_TRACE)).unwrap();
+        let lines: Vec<_> = trace.lines().collect();
+        assert_eq!(lines.len(), 3, "{trace}");
+        assert!(lines[1].ends_with(",9,true"), "{trace}");
+    }
+
+    #[tokio::test]
+    async fn test_run_greedy_timeout_without_anytime() {
+        // intermediate solutions are only tracked, e.g., for `--stop-at-best`
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_GREEDY, vec!["-n".into(), "-a".into()]);
+        exec.track_intermediate = true;
+        let status = exec.run().await.unwrap();
+
+        assert_eq!(status, SolverResult::Timeout);
+        assert!(!exec.scored_from_intermediate());
+    }
+
+    #[tokio::test]
+    async fn test_run_greedy_ok_with_intermediate() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_GREEDY, vec!["-a".into()]);
+        exec.track_intermediate = true;
+        let status = exec.run().await.unwrap();
+
+        assert!(matches!(status, SolverResult::Valid { .. }), "{status:?}");
+        assert!(!exec.scored_from_intermediate());
+        assert!(exec.filename(PATH_TRACE).is_file());
+    }
+
+    #[tokio::test]
+    async fn test_run_greedy_stop_at_target() {
+        // ignores SIGTERM, so it is killed after the grace period
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_GREEDY, vec!["-n".into(), "-a".into()]);
+        exec.track_intermediate = true;
+        exec.target_score = Some(9);
+        exec.timeout = Duration::from_secs(5);
+        let status = exec.run().await.unwrap();
+
+        assert!(matches!(status, SolverResult::Valid { .. }), "{status:?}");
+        assert!(exec.scored_from_intermediate());
+        assert!(exec.target_reached().is_some());
+        assert!(exec.runtime().unwrap() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_run_greedy_stop_at_target_with_output() {
+        // prints its solution upon SIGTERM
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_GREEDY, vec!["-t".into(), "-a".into()]);
+        exec.track_intermediate = true;
+        exec.target_score = Some(9);
+        exec.timeout = Duration::from_secs(5);
+        let status = exec.run().await.unwrap();
+
+        match status {
+            SolverResult::Valid { data } => assert!(data.len() < 9, "{data:?}"),
+            _ => panic!("Unexpected result: {:?}", status),
+        }
+        assert!(!exec.scored_from_intermediate());
+        assert!(exec.target_reached().is_some());
+        assert!(exec.runtime().unwrap() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_run_greedy_stop_at_target_with_malformed_output() {
+        // prints a solution with a wrong cardinality upon SIGTERM
+        #[allow(unused)]
+        let (tmp_dir, mut exec) =
+            default_test_executor(BIN_GREEDY, vec!["-t".into(), "-a".into(), "-w".into()]);
+        exec.track_intermediate = true;
+        exec.target_score = Some(9);
+        exec.timeout = Duration::from_secs(5);
+        let status = exec.run().await.unwrap();
+
+        assert!(
+            matches!(status, SolverResult::SyntaxError { .. }),
+            "{status:?}"
+        );
+        assert!(!exec.scored_from_intermediate());
+        assert!(exec.target_reached().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_run_stop_at_target_with_exit_code() {
+        // a wrapper script writes an intermediate solution with all nodes; the solver then
+        // exits with 128 + SIGTERM upon SIGTERM
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec![]);
+        exec.solver_path = PathBuf::from("/bin/sh");
+        exec.args = vec![
+            "-c".into(),
+            format!(
+                "printf '9\\n1\\n2\\n3\\n4\\n5\\n6\\n7\\n8\\n9\\n' > \"$STRIDE_INTERMEDIATE_PATH\"; \
+                exec {:?} exit 143 --on-sigterm",
+                exec_path(BIN_DUMMY)
+            ),
+        ];
+        exec.track_intermediate = true;
+        exec.target_score = Some(9);
+        exec.timeout = Duration::from_secs(5);
+        let status = exec.run().await.unwrap();
+
+        assert_eq!(
+            status,
+            SolverResult::Valid {
+                data: (1..=9).collect()
+            }
+        );
+        assert!(exec.scored_from_intermediate());
+        assert!(exec.target_reached().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_run_greedy_target_not_reached() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_GREEDY, vec!["-a".into()]);
+        exec.track_intermediate = true;
+        exec.target_score = Some(1);
+        let status = exec.run().await.unwrap();
+
+        assert!(matches!(status, SolverResult::Valid { .. }), "{status:?}");
+        assert!(exec.target_reached().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_memory_limit_exceeded() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) =
+            default_test_executor(BIN_DUMMY, vec!["alloc".into(), "512".into()]);
+        exec.memory_limit_mb = Some(64);
+        let status = exec.run().await.unwrap();
+
+        assert_eq!(status, SolverResult::MemoryLimitExceeded);
+    }
+
+    #[tokio::test]
+    async fn test_run_memory_limit_not_exceeded() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) =
+            default_test_executor(BIN_DUMMY, vec!["alloc".into(), "8".into()]);
+        exec.memory_limit_mb = Some(256);
+        let status = exec.run().await.unwrap();
+
+        // the dummy does not produce any output
+        assert_eq!(status, SolverResult::IncompleteOutput);
+    }
+
+    #[test]
+    fn test_cpu_time_limit_exceeded() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec![]);
+        exec.cpu_time_limit = Some(Duration::from_secs(2));
+        let killed = ExitStatus::from_raw(libc::SIGKILL);
+
+        // killed by someone else before the limit was reached
+        exec.resource_usage = Some(ResourceUsage {
+            user_time: Duration::from_millis(300),
+            ..Default::default()
+        });
+        assert!(!exec.cpu_time_limit_exceeded(killed));
+
+        exec.resource_usage = Some(ResourceUsage {
+            user_time: Duration::from_millis(1500),
+            system_time: Duration::from_millis(600),
+            ..Default::default()
+        });
+        assert!(exec.cpu_time_limit_exceeded(killed));
+        assert!(exec.cpu_time_limit_exceeded(ExitStatus::from_raw(libc::SIGXCPU)));
+        assert!(!exec.cpu_time_limit_exceeded(ExitStatus::from_raw(1 << 8)));
+
+        exec.cpu_time_limit = None;
+        assert!(!exec.cpu_time_limit_exceeded(killed));
+    }
+
+    #[test]
+    fn test_memory_limit_exceeded_requires_failure() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec![]);
+        exec.memory_limit_mb = Some(64);
+        std::fs::write(
+            exec.filename(PATH_STDERR),
+            "warning: almost out of memory\n",
+        )
+        .unwrap();
+
+        exec.resource_usage = Some(ResourceUsage {
+            peak_rss_kb: 60 * 1024,
+            ..Default::default()
+        });
+        assert!(!exec.memory_limit_exceeded(ExitStatus::from_raw(0)).unwrap());
+        assert!(exec
+            .memory_limit_exceeded(ExitStatus::from_raw(1 << 8))
+            .unwrap());
+
+        // a crash far below the limit is not blamed on the limit, even if it mentions memory
+        exec.resource_usage = Some(ResourceUsage {
+            peak_rss_kb: 4 * 1024,
+            ..Default::default()
+        });
+        assert!(!exec
+            .memory_limit_exceeded(ExitStatus::from_raw(1 << 8))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_memory_limit_exceeded_without_message() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec![]);
+        exec.memory_limit_mb = Some(64);
+        std::fs::write(exec.filename(PATH_STDERR), "").unwrap();
+        let aborted = ExitStatus::from_raw(libc::SIGABRT);
+        let segfault = ExitStatus::from_raw(libc::SIGSEGV);
+
+        // silent aborts and segfaults only count close to the limit
+        exec.resource_usage = Some(ResourceUsage {
+            peak_rss_kb: 60 * 1024,
+            ..Default::default()
+        });
+        assert!(exec.memory_limit_exceeded(aborted).unwrap());
+        assert!(exec.memory_limit_exceeded(segfault).unwrap());
+        assert!(!exec
+            .memory_limit_exceeded(ExitStatus::from_raw(1 << 8))
+            .unwrap());
+
+        exec.resource_usage = Some(ResourceUsage {
+            peak_rss_kb: 30 * 1024,
+            ..Default::default()
+        });
+        assert!(!exec.memory_limit_exceeded(aborted).unwrap());
+        assert!(!exec.memory_limit_exceeded(segfault).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_resource_usage() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) =
+            default_test_executor(BIN_DUMMY, vec!["alloc".into(), "64".into()]);
+        exec.run().await.unwrap();
+
+        let usage = exec.resource_usage().unwrap();
+        assert!(usage.peak_rss_kb >= 64 * 1024, "{usage:?}");
+        assert!(
+            !(usage.user_time + usage.system_time).is_zero(),
+            "{usage:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_greedy_syntax_error() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_GREEDY, vec!["-w".into()]);
+        let status = exec.run().await.unwrap();
+
+        match status {
+            SolverResult::SyntaxError { message } => assert!(message.contains("does not match")),
+            _ => panic!("Unexpected result: {:?}", status),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_crashed() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec!["abort".into()]);
+        let status = exec.run().await.unwrap();
+
+        assert_eq!(
+            status,
+            SolverResult::Crashed {
+                signal: libc::SIGABRT
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_non_zero_exit_code() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec!["exit".into(), "3".into()]);
+        let status = exec.run().await.unwrap();
+
+        assert_eq!(status, SolverResult::NonZeroExitCode { code: 3 });
+    }
+
+    #[tokio::test]
+    async fn test_run_interrupted() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec!["sig-term".into()]);
+        let (sender, receiver) = watch::channel(false);
+        exec.interrupt = Some(receiver);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(TIMEOUT_MS / 4)).await;
+            sender.send(true).unwrap();
+            tokio::time::sleep(Duration::from_millis(TIMEOUT_MS)).await; // keep channel open
+        });
+
+        let start = std::time::Instant::now();
+        let status = exec.run().await.unwrap();
+
+        assert_eq!(status, SolverResult::Interrupted);
+        assert!(
+            start.elapsed().as_millis() < TIMEOUT_MS as u128,
+            "{:?}ms",
+            start.elapsed()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_kill_all_process_groups() {
+        #[allow(unused)]
+        let (tmp_dir, mut exec) = default_test_executor(BIN_DUMMY, vec!["never-terminate".into()]);
+        exec.timeout = Duration::from_millis(10 * TIMEOUT_MS);
+        let process_groups = ProcessGroups::default();
+        exec.process_groups = process_groups.clone();
+
+        let killer = process_groups.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(TIMEOUT_MS / 4)).await;
+            killer.kill_all();
+        });
+
+        let start = std::time::Instant::now();
+        exec.run().await.unwrap();
+
+        assert!(
+            start.elapsed().as_millis() < TIMEOUT_MS as u128,
+            "{:?}ms",
+            start.elapsed()
+        );
+        assert!(process_groups.0.lock().unwrap().is_empty());
+    }
 }
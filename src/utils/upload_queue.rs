@@ -0,0 +1,340 @@
+use anyhow::Context;
+use std::{path::Path, time::Duration};
+use tokio::sync::Mutex;
+use tracing::{debug, trace, warn};
+
+use super::{
+    server_connection::ServerConnection,
+    solution_cache_db::SolutionCacheDB,
+    solution_upload::{send_upload_request, UploadStatus},
+    IId,
+};
+
+use rusqlite::Connection;
+
+const RETRY_INITIAL_DELAY: Duration = Duration::from_secs(10);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(300);
+
+/// Persistent queue of uploads that could not be sent, e.g., due to network problems
+pub struct UploadQueue {
+    upload_queue_db: Mutex<Connection>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueuedUpload {
+    id: i64,
+    pub iid: IId,
+    /// Request serialized by `SolutionUploadRequest::to_json`
+    pub request: String,
+    /// Digest of the uploaded solution (see `Solution::compute_digest`), if the request has one
+    pub digest: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlushSummary {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub remaining: usize,
+}
+
+impl UploadQueue {
+    pub async fn new(db_path: &Path) -> anyhow::Result<Self> {
+        let db = Self::connect_or_create_db(db_path).await?;
+        Ok(Self {
+            upload_queue_db: Mutex::new(db),
+        })
+    }
+
+    async fn connect_or_create_db(path: &Path) -> anyhow::Result<Connection> {
+        let already_exists = path.is_file();
+
+        if !already_exists {
+            debug!("Creating database {path:?}");
+        }
+        let connection = Connection::open(path)?;
+
+        trace!("Connection to UploadQueue {path:?} is successful!");
+
+        if !already_exists {
+            debug!("Creating table `Upload` in database {path:?}");
+
+            connection.execute(
+                "CREATE TABLE Upload (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    iid INT NOT NULL,
+                    request TEXT NOT NULL,
+                    digest BLOB NULL,
+                    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+                );",
+                [],
+            )?;
+        }
+
+        Ok(connection)
+    }
+
+    pub async fn push(&self, iid: IId, request: &str, digest: Option<&[u8]>) -> anyhow::Result<()> {
+        let conn = self.upload_queue_db.lock().await;
+        conn.execute(
+            "INSERT INTO Upload (iid, request, digest) VALUES (?1, ?2, ?3)",
+            (iid.iid_to_u32(), request, digest),
+        )
+        .with_context(|| format!("Queueing upload for {iid:?}"))?;
+
+        debug!("Queued upload for {iid:?}");
+        Ok(())
+    }
+
+    /// Returns all queued uploads in the order they were pushed
+    pub async fn pending(&self) -> anyhow::Result<Vec<QueuedUpload>> {
+        let conn = self.upload_queue_db.lock().await;
+        let mut stmt = conn.prepare("SELECT id, iid, request, digest FROM Upload ORDER BY id")?;
+
+        let uploads = stmt
+            .query_map([], |row| {
+                Ok(QueuedUpload {
+                    id: row.get(0)?,
+                    iid: IId::new(row.get(1)?),
+                    request: row.get(2)?,
+                    digest: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(uploads)
+    }
+
+    pub async fn len(&self) -> anyhow::Result<usize> {
+        let conn = self.upload_queue_db.lock().await;
+        let len: usize = conn.query_row("SELECT COUNT(*) FROM Upload", [], |row| row.get(0))?;
+        Ok(len)
+    }
+
+    pub async fn is_empty(&self) -> anyhow::Result<bool> {
+        Ok(self.len().await? == 0)
+    }
+
+    /// Removes all queued uploads and returns how many were dropped
+    pub async fn clear(&self) -> anyhow::Result<usize> {
+        let conn = self.upload_queue_db.lock().await;
+        Ok(conn.execute("DELETE FROM Upload", [])?)
+    }
+
+    async fn remove(&self, upload: &QueuedUpload) -> anyhow::Result<()> {
+        let conn = self.upload_queue_db.lock().await;
+        conn.execute("DELETE FROM Upload WHERE id = ?1", [upload.id])?;
+        Ok(())
+    }
+
+    /// Sends the queued uploads in order. Uploads rejected by the server are dropped;
+    /// if the server cannot be reached, the remaining uploads stay in the queue.
+    /// Accepted solutions are marked as uploaded in the solution cache.
+    pub async fn flush(
+        &self,
+        server_conn: &ServerConnection,
+        solution_cache: &SolutionCacheDB,
+    ) -> anyhow::Result<FlushSummary> {
+        let pending = self.pending().await?;
+        let mut summary = FlushSummary {
+            remaining: pending.len(),
+            ..Default::default()
+        };
+
+        for upload in pending {
+            match send_upload_request(server_conn, upload.iid, &upload.request).await {
+                UploadStatus::Accepted => {
+                    if let Some(digest) = &upload.digest {
+                        solution_cache.mark_uploaded(upload.iid, digest).await?;
+                    }
+                    summary.accepted += 1;
+                }
+                UploadStatus::Rejected => {
+                    warn!("Server rejected queued upload for {:?}", upload.iid);
+                    summary.rejected += 1;
+                }
+                UploadStatus::Failed => break,
+            }
+
+            self.remove(&upload).await?;
+            summary.remaining -= 1;
+        }
+
+        debug!("Flushed upload queue: {summary:?}");
+        Ok(summary)
+    }
+
+    /// Periodically flushes the queue; the delay between attempts doubles while the server
+    /// remains unreachable. Never returns (unless the queue database fails), so abort the task.
+    pub async fn retry_with_backoff(
+        &self,
+        server_conn: &ServerConnection,
+        solution_cache: &SolutionCacheDB,
+    ) -> anyhow::Result<()> {
+        let mut delay = RETRY_INITIAL_DELAY;
+
+        loop {
+            tokio::time::sleep(delay).await;
+
+            if self.is_empty().await? {
+                delay = RETRY_INITIAL_DELAY;
+                continue;
+            }
+
+            let summary = self.flush(server_conn, solution_cache).await?;
+            delay = if summary.remaining > 0 {
+                (2 * delay).min(RETRY_MAX_DELAY)
+            } else {
+                RETRY_INITIAL_DELAY
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+    use tempdir::TempDir;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::pace::Solution;
+
+    const PREFIX: &str = "stride-upload-queue-test";
+
+    #[tokio::test]
+    async fn push_and_pending() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let db_path = tmp_dir.path().join("uploads.db");
+
+        {
+            let queue = UploadQueue::new(db_path.as_path()).await.unwrap();
+            assert!(queue.is_empty().await.unwrap());
+
+            queue.push(IId::new(2), "second", None).await.unwrap();
+            queue
+                .push(IId::new(1), "first", Some(&[1, 2, 3]))
+                .await
+                .unwrap();
+        }
+
+        // the queue survives reconnecting
+        let queue = UploadQueue::new(db_path.as_path()).await.unwrap();
+        assert_eq!(queue.len().await.unwrap(), 2);
+
+        let pending = queue.pending().await.unwrap();
+        assert_eq!(
+            pending
+                .iter()
+                .map(|u| (u.iid, u.request.as_str(), u.digest.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (IId::new(2), "second", None),
+                (IId::new(1), "first", Some(vec![1, 2, 3]))
+            ]
+        );
+
+        queue.remove(&pending[0]).await.unwrap();
+        assert_eq!(queue.pending().await.unwrap(), pending[1..].to_vec());
+
+        assert_eq!(queue.clear().await.unwrap(), 1);
+        assert!(queue.is_empty().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn flush_to_unreachable_server() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let queue = UploadQueue::new(tmp_dir.path().join("uploads.db").as_path())
+            .await
+            .unwrap();
+        let conn = ServerConnection::new("http://127.0.0.1:1".parse().unwrap()).unwrap();
+
+        let solution_cache = SolutionCacheDB::new(tmp_dir.path().join("cache.db").as_path())
+            .await
+            .unwrap();
+
+        queue.push(IId::new(1), "{}", None).await.unwrap();
+        queue.push(IId::new(2), "{}", None).await.unwrap();
+
+        let summary = queue.flush(&conn, &solution_cache).await.unwrap();
+        assert_eq!(
+            summary,
+            FlushSummary {
+                accepted: 0,
+                rejected: 0,
+                remaining: 2
+            }
+        );
+        assert_eq!(queue.len().await.unwrap(), 2);
+    }
+
+    /// Accepts the given number of requests with an empty `200 OK` response
+    fn spawn_accepting_server(requests: usize) -> ServerConnection {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                reader.read_exact(&mut vec![0; content_length]).unwrap();
+
+                (&stream)
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                    .unwrap();
+            }
+        });
+
+        ServerConnection::new(url.parse().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn flush_marks_solutions_as_uploaded() {
+        let tmp_dir = TempDir::new(PREFIX).unwrap();
+        let queue = UploadQueue::new(tmp_dir.path().join("uploads.db").as_path())
+            .await
+            .unwrap();
+        let solution_cache = SolutionCacheDB::new(tmp_dir.path().join("cache.db").as_path())
+            .await
+            .unwrap();
+
+        let iid = IId::new(1);
+        let solution = Solution::from_0indexed_vec(vec![0, 2]);
+        solution_cache
+            .insert(iid, None, Uuid::new_v4(), &solution, false)
+            .await
+            .unwrap();
+        queue
+            .push(iid, "{}", Some(solution.compute_digest().as_slice()))
+            .await
+            .unwrap();
+        assert!(!solution_cache.was_uploaded(iid, &solution).await.unwrap());
+
+        let conn = spawn_accepting_server(1);
+        let summary = queue.flush(&conn, &solution_cache).await.unwrap();
+        assert_eq!(
+            summary,
+            FlushSummary {
+                accepted: 1,
+                rejected: 0,
+                remaining: 0
+            }
+        );
+        assert!(queue.is_empty().await.unwrap());
+        assert!(solution_cache.was_uploaded(iid, &solution).await.unwrap());
+    }
+}